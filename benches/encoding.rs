@@ -0,0 +1,173 @@
+//! Compares the table-driven decoders against the previous `phf` map based implementation.
+//!
+//! Run with `cargo bench --bench encoding`.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ncr::encoding::{Base64rEncoding, Encoding, Mc256Encoding};
+
+/// Size of a typical encrypted chat message.
+const MESSAGE_LEN: usize = 180;
+
+fn message() -> Vec<u8> {
+    (0..MESSAGE_LEN).map(|i| (i * 131 + 7) as u8).collect()
+}
+
+/// The previous base64r decoder: map every character through a `phf` map into a base64 string, then decode it.
+fn legacy_base64r_decode(text: &str) -> Option<Vec<u8>> {
+    let mut output = String::new();
+
+    for ch in text.chars() {
+        output.push(*BASE64R_DECODE.get(&ch)?);
+    }
+
+    STANDARD.decode(output).ok()
+}
+
+/// The previous mc256 decoder: a `phf` map lookup per character.
+fn legacy_mc256_decode(text: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+
+    for ch in text.chars() {
+        output.push(*MC256_DECODE.get(&ch)? as u8);
+    }
+
+    Some(output)
+}
+
+// The previous tables, copied as is.
+
+#[rustfmt::skip]
+const BASE64R_DECODE: phf::Map<char, char> = phf::Map {
+    key: 15467950696543387533u64,
+    disps: &[
+        (11u32, 49u32), (0u32, 64u32), (0u32, 0u32), (0u32, 20u32),
+        (7u32, 44u32), (6u32, 54u32), (10u32, 25u32), (0u32, 1u32),
+        (41u32, 56u32), (1u32, 0u32), (4u32, 35u32), (1u32, 0u32),
+        (45u32, 42u32),
+    ],
+    entries: &[
+        ('·', 'w'), ('¤', 'g'), ('\'', 'G'), ('¸', 'x'),
+        ('«', 'm'), ('^', 'W'), ('¥', 'h'), ('`', 'Y'),
+        ('2', '2'), ('¢', 'e'), ('\\', 'U'), ('7', '7'),
+        ('(', 'H'), (';', 'N'), ('=', 'P'), ('£', 'f'),
+        ('°', 'q'), ('µ', 'u'), ('<', 'O'), ('¡', 'd'),
+        ('¨', 'j'), ('3', '3'), ('¶', 'v'), ('~', 'c'),
+        ('¬', 'n'), ('@', 'S'), ('©', 'k'), ('¯', 'p'),
+        ('9', '9'), ('?', 'R'), ('{', 'Z'), ('|', 'a'),
+        ('ª', 'l'), ('-', 'K'), ('_', 'X'), ('¦', 'i'),
+        ('$', 'D'), ('4', '4'), ('³', 't'), ('[', 'T'),
+        ('8', '8'), ('#', 'C'), ('"', 'B'), ('²', 's'),
+        ('+', '+'), (',', 'J'), ('.', 'L'), (')', 'I'),
+        ('®', 'o'), ('º', 'z'), ('6', '6'), (']', 'V'),
+        ('»', '/'), ('1', '1'), ('0', '0'), ('±', 'r'),
+        ('!', 'A'), ('>', 'Q'), ('¼', 'F'), ('¿', '='),
+        (':', 'M'), ('5', '5'), ('%', 'E'), ('}', 'b'),
+        ('¹', 'y'),
+    ],
+};
+
+#[rustfmt::skip]
+const MC256_DECODE: phf::Map<char, u32> = phf::Map {
+    key: 12913932095322966823u64,
+    disps: &[
+        (0u32, 37u32),  (0u32, 0u32),  (0u32, 136u32),  (0u32, 13u32),
+        (1u32, 1u32),  (2u32, 102u32),  (1u32, 11u32),  (0u32, 37u32),
+        (0u32, 2u32),  (0u32, 105u32),  (0u32, 2u32),  (0u32, 158u32),
+        (0u32, 88u32),  (0u32, 4u32),  (0u32, 116u32),  (1u32, 122u32),
+        (0u32, 0u32),  (0u32, 6u32),  (1u32, 122u32),  (0u32, 202u32),
+        (4u32, 82u32),  (0u32, 126u32),  (0u32, 10u32),  (0u32, 114u32),
+        (0u32, 66u32),  (0u32, 24u32),  (0u32, 71u32),  (0u32, 64u32),
+        (0u32, 7u32),  (0u32, 0u32),  (17u32, 170u32),  (0u32, 1u32),
+        (0u32, 29u32),  (0u32, 195u32),  (2u32, 97u32),  (1u32, 12u32),
+        (0u32, 0u32),  (1u32, 35u32),  (0u32, 74u32),  (0u32, 0u32),
+        (1u32, 179u32),  (0u32, 10u32),  (7u32, 200u32),  (0u32, 5u32),
+        (0u32, 23u32),  (0u32, 40u32),  (8u32, 59u32),  (4u32, 203u32),
+        (3u32, 97u32),  (0u32, 80u32),  (0u32, 96u32),  (3u32, 231u32),
+    ],
+    entries: &[
+        ('ʻ', 113), ('ш', 84), ('✔', 227), ('φ', 59), ('«', 23), ('ο', 55), ('¡', 43), ('ᴢ', 159),
+        ('▲', 244), ('☁', 198), ('у', 79), ('Φ', 50), ('‼', 186), ('ᴏ', 149), ('⚂', 220), ('♧', 16),
+        ('ɴ', 148), ('♮', 216), ('ᛪ', 253), ('‶', 181), ('ѕ', 93), ('™', 132), ('·', 170), ('о', 74),
+        ('≤', 33), ('½', 168), ('⌡', 35), ('\u{AD}', 45), ('⅛', 0), ('ʬ', 224), ('ж', 67), ('∓', 195),
+        ('≠', 48), ('‡', 174), ('ᴛ', 154), ('−', 194), ('д', 65), ('ᴘ', 150), ('⭐', 231), ('♬', 214),
+        ('ᴍ', 147), ('❤', 230), ('¼', 167), ('ᴋ', 145), ('♥', 209), ('г', 64), ('ᴇ', 140), ('ᴡ', 157),
+        ('☵', 202), ('⁷', 123), ('ª', 20), ('♤', 15), ('＋', 104), ('▒', 26), ('ᛩ', 252), ('●', 248),
+        ('ь', 88), ('≥', 32), ('✎', 13), ('░', 25), ('❄', 228), ('×', 49), ('ц', 82), ('ф', 80),
+        ('⚀', 218), ('⁽', 129), ('⛄', 10), ('ꜰ', 141), ('⁊', 98), ('я', 91), ('•', 175), ('☲', 201),
+        ('♡', 17), ('υ', 58), ('→', 101), ('♂', 205), ('⁰', 117), ('‹', 183), ('п', 75), ('‷', 182),
+        ('‵', 180), ('☔', 8), ('∅', 28), ('ι', 52), ('ⁱ', 131), ('◁', 235), ('☠', 135), ('¢', 160),
+        ('♀', 204), ('☀', 197), ('☺', 254), ('❌', 229), ('ы', 87), ('↓', 102), ('ᴄ', 138), ('◆', 236),
+        ('µ', 165), ('‴', 179), ('⅓', 4), ('○', 238), ('⁗', 192), ('♠', 207), ('√', 40), ('⧈', 133),
+        ('н', 73), ('ɪ', 108), ('а', 61), ('ɛ', 107), ('ᴠ', 156), ('☄', 9), ('⁑', 190), ('л', 71),
+        ('э', 89), ('♩', 211), ('»', 24), ('м', 72), ('⚅', 223), ('⁎', 189), ('т', 78), ('∞', 196),
+        ('‐', 171), ('б', 62), ('х', 81), ('ᴅ', 139), ('☽', 203), ('Ψ', 51), ('⸸', 243), ('☈', 199),
+        ('♪', 212), ('⅝', 2), ('‚', 172), ('❣', 14), ('◀', 247), ('π', 56), ('⚐', 12), ('з', 68),
+        ('†', 173), ('⁹', 125), ('₴', 47), ('≡', 30), ('Ө', 111), (';', 115), ('★', 241), ('±', 31),
+        ('κ', 53), ('⅜', 1), ('ʟ', 146), ('↑', 100), ('⚁', 219), ('⅞', 3), ('▼', 246), ('⅔', 5),
+        ('♭', 215), ('ʙ', 137), ('△', 232), ('÷', 36), ('⁺', 126), ('ĸ', 116), ('²', 42), ('ꜱ', 153),
+        ('∙', 39), ('⚓', 251), ('¶', 166), ('℗', 193), ('º', 21), ('і', 94), ('◦', 249), ('ъ', 86),
+        ('ᴊ', 144), ('є', 92), ('р', 76), ('≈', 37), ('ю', 90), ('¬', 22), ('⁻', 127), ('◎', 239),
+        ('←', 99), ('※', 185), ('ʀ', 152), ('ᴀ', 136), ('▓', 27), ('∈', 29), ('☻', 255), ('♦', 210),
+        ('♣', 208), ('▽', 234), ('и', 69), ('⁼', 128), ('⁒', 191), ('⁴', 120), ('′', 177), ('τ', 57),
+        ('⁾', 130), ('ə', 106), ('♢', 18), ('✘', 242), ('³', 119), ('¤', 161), ('Ə', 105), ('⚃', 221),
+        ('„', 96), ('¥', 162), ('е', 66), ('ɢ', 142), ('с', 77), ('⁸', 124), ('Ү', 109), ('ꞯ', 151),
+        ('©', 163), ('ү', 110), ('⁂', 187), ('▷', 233), ('Є', 200), ('☂', 7), ('⁵', 121), ('›', 184),
+        ('ʜ', 143), ('◘', 250), ('¹', 118), ('ᴜ', 155), ('⇄', 103), ('″', 178), ('♫', 213), ('⁶', 122),
+        ('⁉', 188), ('Я', 60), ('ʏ', 158), ('ч', 83), ('щ', 85), ('♯', 217), ('⛏', 226), ('¾', 169),
+        ('☆', 240), ('в', 63), ('…', 97), ('⚄', 222), ('λ', 54), ('·', 46), ('✉', 6), ('ј', 95),
+        ('ⁿ', 41), ('°', 38), ('‱', 176), ('☃', 11), ('⚔', 134), ('⚥', 206), ('▶', 245), ('‰', 44),
+        ('ˌ', 114), ('⌠', 34), ('⛈', 19), ('ө', 112), ('⚡', 225), ('◇', 237), ('®', 164), ('к', 70),
+    ],
+};
+
+fn base64r(c: &mut Criterion) {
+    let encoded = Base64rEncoding::encode(&message());
+    assert_eq!(
+        legacy_base64r_decode(&encoded),
+        Base64rEncoding::decode(&encoded).ok()
+    );
+
+    let mut group = c.benchmark_group("base64r decode");
+    group.bench_function("phf", |b| {
+        b.iter(|| legacy_base64r_decode(black_box(&encoded)))
+    });
+    group.bench_function("table", |b| {
+        b.iter(|| Base64rEncoding::decode(black_box(&encoded)))
+    });
+    group.bench_function("table (reused buffer)", |b| {
+        let mut output = Vec::new();
+        b.iter(|| {
+            output.clear();
+            Base64rEncoding::decode_into(black_box(&encoded), &mut output)
+        })
+    });
+    group.finish();
+}
+
+fn mc256(c: &mut Criterion) {
+    let encoded = Mc256Encoding::encode(&message());
+    assert_eq!(
+        legacy_mc256_decode(&encoded),
+        Mc256Encoding::decode(&encoded).ok()
+    );
+
+    let mut group = c.benchmark_group("mc256 decode");
+    group.bench_function("phf", |b| {
+        b.iter(|| legacy_mc256_decode(black_box(&encoded)))
+    });
+    group.bench_function("table", |b| {
+        b.iter(|| Mc256Encoding::decode(black_box(&encoded)))
+    });
+    group.bench_function("table (reused buffer)", |b| {
+        let mut output = Vec::new();
+        b.iter(|| {
+            output.clear();
+            Mc256Encoding::decode_into(black_box(&encoded), &mut output)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, base64r, mc256);
+criterion_main!(benches);
@@ -1,5 +1,7 @@
 use base64::{engine::general_purpose::STANDARD, Engine};
 use rand::Rng;
+#[cfg(feature = "mnemonic")]
+use std::fmt;
 
 use crate::NcrError;
 
@@ -53,6 +55,95 @@ impl AesKey {
             .try_into()
             .map_or_else(|_| Err(NcrError::DecodeError), |value| Ok(Self(value)))
     }
+
+    /// Encode the key as 12 words from the [BIP39](https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki) english word list.
+    ///
+    /// The last word contains a 4 bits checksum, so most typos are detected when decoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ncr::AesKey;
+    ///
+    /// let key = AesKey::from([0u8; 16]);
+    /// let words = key.encode_mnemonic();
+    ///
+    /// assert_eq!(words, "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about");
+    /// assert_eq!(AesKey::decode_mnemonic(&words).unwrap(), key);
+    /// ```
+    #[cfg(feature = "mnemonic")]
+    pub fn encode_mnemonic(&self) -> String {
+        bip39::Mnemonic::from_entropy(&self.0).unwrap().to_string()
+    }
+
+    /// Decode the key from 12 words of the BIP39 english word list.
+    ///
+    /// Words are case insensitive and can be separated by any whitespace.
+    ///
+    /// # Error
+    ///
+    /// This return a error if there isn't 12 words, a word isn't in the word list, or the checksum doesn't match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ncr::{AesKey, MnemonicError};
+    ///
+    /// let typo = "abandon abandon abandon abandon abandn abandon abandon abandon abandon abandon abandon about";
+    /// assert_eq!(AesKey::decode_mnemonic(typo), Err(MnemonicError::UnknownWord(4, "abandn".to_owned())));
+    ///
+    /// let swapped = "about abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+    /// assert_eq!(AesKey::decode_mnemonic(swapped), Err(MnemonicError::Checksum));
+    /// ```
+    #[cfg(feature = "mnemonic")]
+    pub fn decode_mnemonic(words: &str) -> Result<Self, MnemonicError> {
+        let words = words.to_lowercase();
+
+        let count = words.split_whitespace().count();
+        if count != 12 {
+            return Err(MnemonicError::WordCount(count));
+        }
+
+        let mnemonic = bip39::Mnemonic::parse_in_normalized(bip39::Language::English, &words)
+            .map_err(|error| match error {
+                bip39::Error::UnknownWord(position) => MnemonicError::UnknownWord(
+                    position,
+                    words.split_whitespace().nth(position).unwrap().to_owned(),
+                ),
+                _ => MnemonicError::Checksum,
+            })?;
+
+        let (entropy, _) = mnemonic.to_entropy_array();
+        let mut key = [0u8; 16];
+        key.copy_from_slice(&entropy[..16]);
+
+        Ok(Self(key))
+    }
+}
+
+/// This represents all errors that can happen when decoding a key from words.
+#[cfg(feature = "mnemonic")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MnemonicError {
+    /// The number of words isn't 12.
+    WordCount(usize),
+    /// A word (at the given position, starting from 0) isn't in the word list.
+    UnknownWord(usize, String),
+    /// The checksum doesn't match, a word is probably wrong or misplaced.
+    Checksum,
+}
+
+#[cfg(feature = "mnemonic")]
+impl fmt::Display for MnemonicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MnemonicError::WordCount(count) => write!(f, "Expected 12 words, got {count}"),
+            MnemonicError::UnknownWord(position, word) => {
+                write!(f, "Unknown word {word:?} at position {position}")
+            }
+            MnemonicError::Checksum => write!(f, "Checksum mismatch"),
+        }
+    }
 }
 
 impl From<[u8; 16]> for AesKey {
@@ -0,0 +1,224 @@
+//! Find weaknesses in captured ciphertexts, without the key.
+//!
+//! Some settings leak information at scale:
+//!
+//! - Aes/cfb8 derives the IV from a 8 bytes nonce through `java.util.Random`, which only keeps 48 bits of it.
+//!   Nonces differing only in their top 16 bits give the same IV ([FindingKind::RepeatedIv]),
+//!   and messages sharing an IV leak the xor of their plaintexts.
+//! - Aes/ecb encrypts identical 16 bytes blocks to identical blocks ([FindingKind::RepeatedBlock]).
+//! - Aes/gcm leaks the authentication key when an IV is repeated, allowing forgeries ([FindingKind::RepeatedIv]).
+//!
+//! [audit] scans a set of ciphertexts for these patterns, so members can be told when their settings are weak.
+//!
+//! # Examples
+//!
+//! ```
+//! use ncr::{
+//!     audit::{audit, FindingKind, Mode},
+//!     encoding::{Base64rEncoding, Encoding},
+//!     encryption::{EcbEncryption, Encryption},
+//!     AesKey,
+//! };
+//!
+//! let key = AesKey::gen_from_passphrase(b"secret");
+//!
+//! let messages = [
+//!     EcbEncryption::<Base64rEncoding>::encrypt("#%Hello, world!!", &key).unwrap(),
+//!     EcbEncryption::<Base64rEncoding>::encrypt("#%Hello, world!!", &key).unwrap(),
+//!     // The first 16 bytes are the same as above.
+//!     EcbEncryption::<Base64rEncoding>::encrypt("#%Hello, world!!How are you?", &key).unwrap(),
+//! ];
+//!
+//! let findings = audit::<Base64rEncoding, _>(&messages, Mode::Ecb);
+//!
+//! assert_eq!(findings[0].kind, FindingKind::Duplicate);
+//! assert_eq!(findings[0].messages, [0, 1]);
+//! assert_eq!(findings[1].kind, FindingKind::RepeatedBlock);
+//! assert_eq!(findings[1].messages, [0, 2]);
+//! ```
+//!
+//! ## Cfb8 IVs
+//!
+//! ```
+//! use ncr::{
+//!     audit::{audit, FindingKind, Mode},
+//!     encoding::{Base64rEncoding, Encoding},
+//! };
+//!
+//! // Only the nonce (the first 8 bytes) matters.
+//! let messages = [
+//!     Base64rEncoding::encode(&[0x00, 0x00, 1, 2, 3, 4, 5, 6, 0xAA]),
+//!     Base64rEncoding::encode(&[0x12, 0x34, 1, 2, 3, 4, 5, 6, 0xBB]),
+//!     Base64rEncoding::encode(&[0x00, 0x00, 6, 5, 4, 3, 2, 1, 0xCC]),
+//!     Base64rEncoding::encode(&[0x00, 0x00, 6, 5, 4, 3, 2, 1, 0xDD]),
+//! ];
+//!
+//! let findings = audit::<Base64rEncoding, _>(&messages, Mode::Cfb8);
+//!
+//! assert_eq!(findings[0].kind, FindingKind::RepeatedNonce);
+//! assert_eq!(findings[0].messages, [2, 3]);
+//! assert_eq!(findings[1].kind, FindingKind::RepeatedIv);
+//! assert_eq!(findings[1].messages, [0, 1]);
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{encoding::Encoding, JavaRandom};
+
+/// The encryption the ciphertexts were made with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    Cfb8,
+    Ecb,
+    Gcm,
+}
+
+/// The kind of a weakness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FindingKind {
+    /// The ciphertexts are identical (a replayed message, or identical plaintexts with aes/ecb).
+    Duplicate,
+    /// The ciphertexts use the same nonce (aes/cfb8).
+    RepeatedNonce,
+    /// The ciphertexts use the same IV (aes/cfb8 with different nonces, or aes/gcm).
+    RepeatedIv,
+    /// The ciphertexts contain the same 16 bytes block (aes/ecb).
+    RepeatedBlock,
+    /// The ciphertexts can't be decoded, or are too short for the mode.
+    Malformed,
+}
+
+/// A weakness found in some ciphertexts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Finding {
+    pub kind: FindingKind,
+    /// The indices of the ciphertexts involved, in order.
+    pub messages: Vec<usize>,
+}
+
+/// Scan ciphertexts for weaknesses.
+///
+/// Identical ciphertexts are reported once as [FindingKind::Duplicate], and only the first of them is scanned further.
+/// Findings are sorted by kind, then by their first message.
+///
+/// See the [module documentation](self).
+pub fn audit<E: Encoding, S: AsRef<str>>(ciphertexts: &[S], mode: Mode) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut malformed = Vec::new();
+
+    // Group identical ciphertexts.
+    let mut distinct: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+    for (index, ciphertext) in ciphertexts.iter().enumerate() {
+        match E::decode(ciphertext.as_ref()) {
+            Ok(bytes) if is_well_formed(&bytes, mode) => {
+                distinct.entry(bytes).or_default().push(index)
+            }
+            _ => malformed.push(index),
+        }
+    }
+
+    let mut messages: Vec<(usize, &[u8])> = Vec::with_capacity(distinct.len());
+    for (bytes, indices) in &distinct {
+        if indices.len() > 1 {
+            findings.push(Finding {
+                kind: FindingKind::Duplicate,
+                messages: indices.clone(),
+            });
+        }
+        messages.push((indices[0], bytes));
+    }
+    messages.sort_unstable();
+
+    match mode {
+        Mode::Cfb8 => audit_cfb8(&messages, &mut findings),
+        Mode::Ecb => audit_ecb(&messages, &mut findings),
+        Mode::Gcm => audit_gcm(&messages, &mut findings),
+    }
+
+    if !malformed.is_empty() {
+        findings.push(Finding {
+            kind: FindingKind::Malformed,
+            messages: malformed,
+        });
+    }
+
+    findings.sort_unstable_by_key(|finding| (finding.kind, finding.messages[0]));
+    findings
+}
+
+fn is_well_formed(bytes: &[u8], mode: Mode) -> bool {
+    match mode {
+        Mode::Cfb8 => bytes.len() >= 8,
+        Mode::Ecb => !bytes.is_empty() && bytes.len().is_multiple_of(16),
+        Mode::Gcm => bytes.len() >= 24,
+    }
+}
+
+fn audit_cfb8(messages: &[(usize, &[u8])], findings: &mut Vec<Finding>) {
+    let mut ivs: HashMap<[u8; 16], Vec<(usize, u64)>> = HashMap::new();
+    for &(index, bytes) in messages {
+        let nonce = u64::from_be_bytes(bytes[..8].try_into().unwrap());
+
+        let mut iv = [0u8; 16];
+        JavaRandom::new(nonce as i64).next_bytes(&mut iv);
+
+        ivs.entry(iv).or_default().push((index, nonce));
+    }
+
+    for group in ivs.into_values().filter(|group| group.len() > 1) {
+        let same_nonce = group.iter().all(|&(_, nonce)| nonce == group[0].1);
+
+        findings.push(Finding {
+            kind: if same_nonce {
+                FindingKind::RepeatedNonce
+            } else {
+                FindingKind::RepeatedIv
+            },
+            messages: group.into_iter().map(|(index, _)| index).collect(),
+        });
+    }
+}
+
+fn audit_ecb(messages: &[(usize, &[u8])], findings: &mut Vec<Finding>) {
+    let mut blocks: HashMap<&[u8], Vec<usize>> = HashMap::new();
+    for &(index, bytes) in messages {
+        for block in bytes.chunks_exact(16) {
+            blocks.entry(block).or_default().push(index);
+        }
+    }
+
+    let mut repeated: Vec<Vec<usize>> = blocks
+        .into_values()
+        .filter(|indices| indices.len() > 1)
+        .map(|mut indices| {
+            // A block repeated inside a single message is reported with that message alone.
+            indices.dedup();
+            indices
+        })
+        .collect();
+
+    // The same messages may share several blocks, report them once.
+    repeated.sort_unstable();
+    repeated.dedup();
+
+    findings.extend(repeated.into_iter().map(|messages| Finding {
+        kind: FindingKind::RepeatedBlock,
+        messages,
+    }));
+}
+
+fn audit_gcm(messages: &[(usize, &[u8])], findings: &mut Vec<Finding>) {
+    let mut ivs: HashMap<&[u8], Vec<usize>> = HashMap::new();
+    for &(index, bytes) in messages {
+        ivs.entry(&bytes[..12]).or_default().push(index);
+    }
+
+    findings.extend(
+        ivs.into_values()
+            .filter(|indices| indices.len() > 1)
+            .map(|messages| Finding {
+                kind: FindingKind::RepeatedIv,
+                messages,
+            }),
+    );
+}
@@ -0,0 +1,347 @@
+//! Parse chat lines from arbitrary server chat formats.
+//!
+//! Servers format chat in many ways (`<Name> msg`, `[Rank] Name » msg`, `Name: msg`),
+//! so the ciphertext has to be separated from the sender before it can be decrypted.
+//!
+//! A [ChatFormat] is described by a template, where `{sender}` matches the sender name,
+//! `{message}` matches the message, `{*}` matches anything, and everything else matches literally.
+//! A [ChatParser] tries a list of formats in order.
+//!
+//! Formatting codes should be removed with [strip_formatting](crate::utils::strip_formatting) before parsing.
+//!
+//! # Examples
+//!
+//! ```
+//! use ncr::chat::{ChatFormat, ChatParser};
+//!
+//! let parser = ChatParser::default();
+//!
+//! let line = parser.parse("[Admin] Steve » Hello, world!").unwrap();
+//! assert_eq!(line.prefix, "[Admin] Steve » ");
+//! assert_eq!(line.sender, Some("Steve"));
+//! assert_eq!(line.message, "Hello, world!");
+//!
+//! // Stacked tags.
+//! let line = parser.parse("[A] [B] Steve » hi").unwrap();
+//! assert_eq!(line.sender, Some("Steve"));
+//! assert_eq!(line.message, "hi");
+//!
+//! // Custom formats are tried before the presets.
+//! let parser = ChatParser::default().with_format(ChatFormat::new("{sender} whispers: {message}").unwrap());
+//!
+//! let line = parser.parse("Alex whispers: Hi").unwrap();
+//! assert_eq!(line.sender, Some("Alex"));
+//! assert_eq!(line.message, "Hi");
+//! ```
+//!
+//! ## Decrypting
+//!
+//! ```
+//! use ncr::{
+//!     chat::ChatParser,
+//!     encoding::Base64rEncoding,
+//!     encryption::{Cfb8Cipher, Cipher},
+//!     utils::prepend_header,
+//!     AesKey,
+//! };
+//!
+//! let cipher = Cfb8Cipher::<Base64rEncoding>::new(&AesKey::gen_from_passphrase(b"secret"));
+//! let ciphertext = cipher.encrypt(&prepend_header("I love Minecraft!")).unwrap();
+//!
+//! let line = format!("<Steve> {}", ciphertext);
+//! let decrypted = ChatParser::default().decrypt(&line, &cipher).unwrap();
+//!
+//! assert_eq!(decrypted.line.sender, Some("Steve"));
+//! assert_eq!(decrypted.plaintext, "I love Minecraft!");
+//! ```
+
+use std::fmt;
+
+use crate::{encryption::Cipher, utils::trim_header};
+
+/// A chat line, split into its parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChatLine<'a> {
+    /// Everything before the message, including the sender.
+    pub prefix: &'a str,
+    /// The sender, if the format has one.
+    pub sender: Option<&'a str>,
+    /// The message.
+    pub message: &'a str,
+}
+
+/// A chat line with a decrypted message, returned by [ChatParser::decrypt].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecryptedLine<'a> {
+    /// The chat line, where the message is the ciphertext.
+    pub line: ChatLine<'a>,
+    /// The plaintext, with the header removed.
+    pub plaintext: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Sender,
+    Message,
+    Any,
+}
+
+/// A chat format, described by a template.
+///
+/// See the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatFormat {
+    parts: Vec<Part>,
+}
+
+impl ChatFormat {
+    /// Vanilla chat: `<Name> msg`.
+    pub const VANILLA: &'static str = "<{sender}> {message}";
+    /// Chat with a rank: `[Rank] Name » msg`.
+    pub const RANK_ARROW: &'static str = "[{*}] {sender} » {message}";
+    /// `Name » msg`.
+    pub const ARROW: &'static str = "{sender} » {message}";
+    /// Chat with a rank: `[Rank] Name: msg`.
+    pub const RANK_COLON: &'static str = "[{*}] {sender}: {message}";
+    /// `Name: msg`.
+    pub const COLON: &'static str = "{sender}: {message}";
+
+    /// Parse a template.
+    ///
+    /// # Error
+    ///
+    /// This return a error if the template doesn't contain exactly one `{message}`, contains more than one `{sender}`,
+    /// contains an unknown placeholder, or has two placeholders next to each other.
+    pub fn new(template: &str) -> Result<Self, FormatError> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+
+        while !rest.is_empty() {
+            let part = if let Some(after) = rest.strip_prefix('{') {
+                let end = after.find('}').ok_or(FormatError::UnknownPlaceholder)?;
+                rest = &after[end + 1..];
+
+                match &after[..end] {
+                    "sender" => Part::Sender,
+                    "message" => Part::Message,
+                    "*" => Part::Any,
+                    _ => return Err(FormatError::UnknownPlaceholder),
+                }
+            } else {
+                let end = rest.find('{').unwrap_or(rest.len());
+                let literal = rest[..end].to_owned();
+                rest = &rest[end..];
+
+                Part::Literal(literal)
+            };
+
+            // Without a literal in between, there's no way to tell where a placeholder ends.
+            if !matches!(part, Part::Literal(_))
+                && !matches!(parts.last(), None | Some(Part::Literal(_)))
+            {
+                return Err(FormatError::AdjacentPlaceholders);
+            }
+
+            parts.push(part);
+        }
+
+        match parts.iter().filter(|part| **part == Part::Message).count() {
+            0 => return Err(FormatError::MissingMessage),
+            1 => {}
+            _ => return Err(FormatError::DuplicatePlaceholder),
+        }
+        if parts.iter().filter(|part| **part == Part::Sender).count() > 1 {
+            return Err(FormatError::DuplicatePlaceholder);
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Split a chat line according to this format.
+    ///
+    /// `{sender}` and `{message}` followed by a literal end at the first occurrence of that literal.
+    /// `{*}` followed by a literal tries every occurrence of that literal until the rest of the line matches,
+    /// so `[{*}] {sender}` also matches stacked tags like `[Admin] [VIP] Steve`.
+    /// The sender can't be empty or contain whitespace, and the message can't be empty.
+    ///
+    /// Returns `None` if the line doesn't match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ncr::chat::ChatFormat;
+    ///
+    /// let format = ChatFormat::new(ChatFormat::RANK_ARROW).unwrap();
+    ///
+    /// let line = format.parse("[Admin] [VIP] Steve » Hello, [world]!").unwrap();
+    /// assert_eq!(line.sender, Some("Steve"));
+    /// assert_eq!(line.message, "Hello, [world]!");
+    ///
+    /// assert_eq!(format.parse("Steve » Hello, world!"), None);
+    /// ```
+    pub fn parse<'a>(&self, line: &'a str) -> Option<ChatLine<'a>> {
+        let mut parsed = ChatLine {
+            prefix: "",
+            sender: None,
+            message: "",
+        };
+
+        self.parse_from(0, line, line, &mut parsed)
+            .then_some(parsed)
+    }
+
+    /// Match the parts starting at `i` against `rest`, the end of `line`.
+    fn parse_from<'a>(
+        &self,
+        i: usize,
+        line: &'a str,
+        rest: &'a str,
+        parsed: &mut ChatLine<'a>,
+    ) -> bool {
+        let Some(part) = self.parts.get(i) else {
+            return rest.is_empty();
+        };
+
+        if *part == Part::Message {
+            parsed.prefix = &line[..line.len() - rest.len()];
+        }
+
+        let literal = match part {
+            Part::Literal(literal) => {
+                return rest
+                    .strip_prefix(literal.as_str())
+                    .is_some_and(|rest| self.parse_from(i + 1, line, rest, parsed));
+            }
+            _ => match self.parts.get(i + 1) {
+                Some(Part::Literal(literal)) => Some(literal.as_str()),
+                _ => None,
+            },
+        };
+
+        let mut matches = |end: usize| {
+            let (matched, after) = rest.split_at(end);
+
+            match part {
+                Part::Sender if matched.is_empty() || matched.contains(char::is_whitespace) => {
+                    return false
+                }
+                Part::Sender => parsed.sender = Some(matched),
+                Part::Message if matched.is_empty() => return false,
+                Part::Message => parsed.message = matched,
+                _ => {}
+            }
+
+            self.parse_from(i + 1, line, after, parsed)
+        };
+
+        match literal {
+            None => matches(rest.len()),
+            Some(literal) if *part == Part::Any => {
+                rest.match_indices(literal).any(|(end, _)| matches(end))
+            }
+            Some(literal) => rest.find(literal).is_some_and(matches),
+        }
+    }
+}
+
+/// A list of chat formats, tried in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatParser {
+    formats: Vec<ChatFormat>,
+}
+
+impl ChatParser {
+    /// Create a parser with all the built-in formats.
+    pub fn new() -> Self {
+        let formats = [
+            ChatFormat::VANILLA,
+            ChatFormat::RANK_ARROW,
+            ChatFormat::ARROW,
+            ChatFormat::RANK_COLON,
+            ChatFormat::COLON,
+        ];
+
+        Self {
+            formats: formats
+                .into_iter()
+                .map(|template| ChatFormat::new(template).unwrap())
+                .collect(),
+        }
+    }
+
+    /// Create a parser without any format, to be filled with [ChatParser::with_format].
+    ///
+    /// It only decrypts bare ciphertexts until a format is added.
+    pub fn empty() -> Self {
+        Self {
+            formats: Vec::new(),
+        }
+    }
+
+    /// Add a format, which is tried before all existing formats.
+    pub fn with_format(mut self, format: ChatFormat) -> Self {
+        self.formats.insert(0, format);
+        self
+    }
+
+    /// Split a chat line using the first matching format.
+    pub fn parse<'a>(&self, line: &'a str) -> Option<ChatLine<'a>> {
+        self.formats.iter().find_map(|format| format.parse(line))
+    }
+
+    /// Find a format whose message can be decrypted, and decrypt it.
+    ///
+    /// Formats are tried in order, and a message only counts as decrypted if it starts with the "#%" header.
+    /// If no format matches, the whole line is tried as a bare ciphertext.
+    pub fn decrypt<'a, C: Cipher>(&self, line: &'a str, cipher: &C) -> Option<DecryptedLine<'a>> {
+        let bare = ChatLine {
+            prefix: "",
+            sender: None,
+            message: line,
+        };
+
+        self.formats
+            .iter()
+            .filter_map(|format| format.parse(line))
+            .chain(std::iter::once(bare))
+            .find_map(|line| {
+                let plaintext = cipher.decrypt(line.message.trim()).ok()?;
+                let plaintext = trim_header(&plaintext).ok()?.to_owned();
+
+                Some(DecryptedLine { line, plaintext })
+            })
+    }
+}
+
+impl Default for ChatParser {
+    /// A parser with all the built-in formats, same as [ChatParser::new].
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// This represents all errors that can happen when parsing a [ChatFormat] template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    MissingMessage,
+    DuplicatePlaceholder,
+    UnknownPlaceholder,
+    AdjacentPlaceholders,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                FormatError::MissingMessage => "Missing message placeholder",
+                FormatError::DuplicatePlaceholder => "Duplicate placeholder",
+                FormatError::UnknownPlaceholder => "Unknown placeholder",
+                FormatError::AdjacentPlaceholders => "Adjacent placeholders",
+            }
+        )
+    }
+}
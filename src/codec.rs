@@ -0,0 +1,171 @@
+//! A line-based codec for chat streams.
+//!
+//! [NcrCodec] implements [Decoder] and [Encoder] from [tokio-util](https://docs.rs/tokio-util),
+//! so it can be used with `FramedRead`, `FramedWrite` or `Framed` to turn a stream of raw chat lines
+//! into a stream of [ChatMessage]s, and to encrypt outgoing lines.
+//!
+//! # Examples
+//!
+//! ```
+//! use bytes::BytesMut;
+//! use ncr::{
+//!     codec::{ChatMessage, NcrCodec},
+//!     encoding::Base64rEncoding,
+//!     encryption::Cfb8Cipher,
+//!     AesKey,
+//! };
+//! use tokio_util::codec::{Decoder, Encoder};
+//!
+//! let key = AesKey::gen_from_passphrase(b"secret");
+//! let mut codec = NcrCodec::new(Cfb8Cipher::<Base64rEncoding>::new(&key));
+//!
+//! let mut buf = BytesMut::new();
+//! codec.encode("I love Minecraft!", &mut buf).unwrap();
+//! buf.extend_from_slice(b"Hello, world!\n");
+//!
+//! assert_eq!(
+//!     codec.decode(&mut buf).unwrap(),
+//!     Some(ChatMessage::Decrypted("I love Minecraft!".to_owned()))
+//! );
+//! assert_eq!(
+//!     codec.decode(&mut buf).unwrap(),
+//!     Some(ChatMessage::Plain("Hello, world!".to_owned()))
+//! );
+//! assert_eq!(codec.decode(&mut buf).unwrap(), None);
+//! ```
+
+use bytes::BytesMut;
+use std::{error, fmt, io};
+use tokio_util::codec::{Decoder, Encoder, LinesCodec, LinesCodecError};
+
+use crate::{
+    encryption::Cipher,
+    utils::{prepend_header, trim_header},
+    NcrError,
+};
+
+/// A line received from a chat stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatMessage {
+    /// A line that was encrypted, with the header already removed.
+    Decrypted(String),
+    /// A line that isn't encrypted (or can't be decrypted with this key), untouched.
+    Plain(String),
+}
+
+/// A codec that decrypts incoming lines and encrypts outgoing lines.
+///
+/// The "#%" header is prepended before encrypting, and removed after decrypting.
+/// Lines that can't be decrypted are passed through as [ChatMessage::Plain].
+#[derive(Debug)]
+pub struct NcrCodec<C: Cipher> {
+    cipher: C,
+    lines: LinesCodec,
+}
+
+impl<C: Cipher> NcrCodec<C> {
+    /// Create a codec without a maximum line length.
+    pub fn new(cipher: C) -> Self {
+        Self {
+            cipher,
+            lines: LinesCodec::new(),
+        }
+    }
+
+    /// Create a codec with a maximum line length.
+    ///
+    /// See [LinesCodec::new_with_max_length].
+    pub fn new_with_max_length(cipher: C, max_length: usize) -> Self {
+        Self {
+            cipher,
+            lines: LinesCodec::new_with_max_length(max_length),
+        }
+    }
+
+    /// Returns the underlying cipher.
+    #[inline]
+    pub fn cipher(&self) -> &C {
+        &self.cipher
+    }
+
+    fn classify(&self, line: String) -> ChatMessage {
+        match self.cipher.decrypt(&line) {
+            Ok(plaintext) => match trim_header(&plaintext) {
+                Ok(text) => ChatMessage::Decrypted(text.to_owned()),
+                Err(_) => ChatMessage::Plain(line),
+            },
+            Err(_) => ChatMessage::Plain(line),
+        }
+    }
+}
+
+impl<C: Cipher> Decoder for NcrCodec<C> {
+    type Item = ChatMessage;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<ChatMessage>, CodecError> {
+        Ok(self.lines.decode(src)?.map(|line| self.classify(line)))
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<ChatMessage>, CodecError> {
+        Ok(self.lines.decode_eof(src)?.map(|line| self.classify(line)))
+    }
+}
+
+impl<C: Cipher, T: AsRef<str>> Encoder<T> for NcrCodec<C> {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<(), CodecError> {
+        let ciphertext = self.cipher.encrypt(&prepend_header(item.as_ref()))?;
+
+        Ok(self.lines.encode(ciphertext, dst)?)
+    }
+}
+
+/// This represents all errors that can happen in [NcrCodec].
+#[derive(Debug)]
+pub enum CodecError {
+    /// Error from reading or writing lines.
+    Lines(LinesCodecError),
+    /// Error from encrypting a line.
+    Ncr(NcrError),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Lines(err) => write!(f, "{err}"),
+            CodecError::Ncr(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl error::Error for CodecError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CodecError::Lines(err) => Some(err),
+            CodecError::Ncr(_) => None,
+        }
+    }
+}
+
+impl From<LinesCodecError> for CodecError {
+    #[inline]
+    fn from(value: LinesCodecError) -> Self {
+        CodecError::Lines(value)
+    }
+}
+
+impl From<NcrError> for CodecError {
+    #[inline]
+    fn from(value: NcrError) -> Self {
+        CodecError::Ncr(value)
+    }
+}
+
+impl From<io::Error> for CodecError {
+    #[inline]
+    fn from(value: io::Error) -> Self {
+        CodecError::Lines(value.into())
+    }
+}
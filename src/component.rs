@@ -0,0 +1,174 @@
+//! Decrypt messages inside Minecraft json text components.
+//!
+//! Servers send chat as text components, such as
+//! `{"translate":"chat.type.text","with":[{"text":"Steve"},"<ciphertext>"]}`.
+//! [decrypt_component] walks the component tree, finds the part that contains the ciphertext,
+//! and returns a new component with the plaintext in its place.
+//!
+//! # Examples
+//!
+//! ```
+//! use ncr::{
+//!     component::decrypt_component,
+//!     encoding::Base64rEncoding,
+//!     encryption::{Cfb8Cipher, Cipher},
+//!     utils::prepend_header,
+//!     AesKey,
+//! };
+//! use serde_json::json;
+//!
+//! let cipher = Cfb8Cipher::<Base64rEncoding>::new(&AesKey::gen_from_passphrase(b"secret"));
+//! let ciphertext = cipher.encrypt(&prepend_header("I love Minecraft!")).unwrap();
+//!
+//! let component = json!({
+//!     "translate": "chat.type.text",
+//!     "with": [{ "text": "Steve" }, ciphertext],
+//! });
+//!
+//! let decrypted = decrypt_component(&component, &cipher, false).unwrap();
+//!
+//! assert_eq!(decrypted.plaintext, "I love Minecraft!");
+//! assert_eq!(
+//!     decrypted.component,
+//!     json!({
+//!         "translate": "chat.type.text",
+//!         "with": [{ "text": "Steve" }, { "text": "I love Minecraft!" }],
+//!     })
+//! );
+//!
+//! // Show the original ciphertext when hovering over the message.
+//! let decrypted = decrypt_component(&component, &cipher, true).unwrap();
+//!
+//! assert_eq!(
+//!     decrypted.component["with"][1]["hoverEvent"]["contents"],
+//!     ciphertext
+//! );
+//! ```
+
+use serde_json::{json, Map, Value};
+
+use crate::{encryption::Cipher, utils::trim_header};
+
+/// The result of [decrypt_component].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecryptedComponent {
+    /// The component, with the ciphertext replaced by the plaintext.
+    pub component: Value,
+    /// The plaintext, with the header removed.
+    pub plaintext: String,
+    /// The ciphertext, as found in the component.
+    pub ciphertext: String,
+}
+
+/// Find and decrypt the ciphertext in a text component.
+///
+/// Every part of the component tree is tried, starting from the root:
+/// first the text of the part as a whole (servers sometimes split a message into differently styled parts),
+/// then each of its children (`with` arguments, then `extra`).
+/// The first part that decrypts to a text starting with the "#%" header is replaced.
+///
+/// If `hover` is true, the replaced part gets a hover event showing the original ciphertext.
+///
+/// Returns `None` if no part of the component can be decrypted.
+pub fn decrypt_component<C: Cipher>(
+    component: &Value,
+    cipher: &C,
+    hover: bool,
+) -> Option<DecryptedComponent> {
+    let mut component = component.clone();
+    let (plaintext, ciphertext) = visit(&mut component, cipher, hover)?;
+
+    Some(DecryptedComponent {
+        component,
+        plaintext,
+        ciphertext,
+    })
+}
+
+/// Get the plain text of a component, ignoring styles.
+///
+/// Translatable components are not translated, only their `with` arguments are included.
+pub fn plain_text(component: &Value) -> String {
+    let mut output = String::new();
+    push_plain_text(component, &mut output);
+
+    output
+}
+
+fn push_plain_text(component: &Value, output: &mut String) {
+    match component {
+        Value::String(text) => output.push_str(text),
+        Value::Array(parts) => parts.iter().for_each(|part| push_plain_text(part, output)),
+        Value::Object(object) => {
+            if let Some(Value::String(text)) = object.get("text") {
+                output.push_str(text);
+            }
+            for key in ["with", "extra"] {
+                if let Some(Value::Array(parts)) = object.get(key) {
+                    parts.iter().for_each(|part| push_plain_text(part, output));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn try_decrypt<C: Cipher>(text: &str, cipher: &C) -> Option<String> {
+    let plaintext = cipher.decrypt(text.trim()).ok()?;
+
+    trim_header(&plaintext).ok().map(str::to_owned)
+}
+
+/// Returns the plaintext and the ciphertext if `component` (or one of its children) was replaced.
+fn visit<C: Cipher>(component: &mut Value, cipher: &C, hover: bool) -> Option<(String, String)> {
+    // The text of a translatable component is its key, it can't be the message.
+    let translatable =
+        matches!(component, Value::Object(object) if object.contains_key("translate"));
+
+    if !translatable {
+        let ciphertext = plain_text(component);
+
+        if let Some(plaintext) = try_decrypt(&ciphertext, cipher) {
+            *component = replacement(component, &plaintext, &ciphertext, hover);
+            return Some((plaintext, ciphertext));
+        }
+    }
+
+    match component {
+        Value::Array(parts) => parts.iter_mut().find_map(|part| visit(part, cipher, hover)),
+        Value::Object(object) => {
+            ["with", "extra"]
+                .into_iter()
+                .find_map(|key| match object.get_mut(key) {
+                    Some(Value::Array(parts)) => {
+                        parts.iter_mut().find_map(|part| visit(part, cipher, hover))
+                    }
+                    _ => None,
+                })
+        }
+        _ => None,
+    }
+}
+
+/// Build the component that replaces `original`, keeping its style.
+fn replacement(original: &Value, plaintext: &str, ciphertext: &str, hover: bool) -> Value {
+    let mut object = match original {
+        Value::Object(object) => {
+            let mut object = object.clone();
+            object.remove("extra");
+            object
+        }
+        _ => Map::new(),
+    };
+
+    object.insert("text".to_owned(), Value::String(plaintext.to_owned()));
+
+    if hover {
+        object.insert(
+            "hoverEvent".to_owned(),
+            json!({ "action": "show_text", "contents": ciphertext }),
+        );
+    }
+
+    Value::Object(object)
+}
@@ -0,0 +1,356 @@
+use std::{fmt, marker::PhantomData};
+
+use super::{table::DecodeTable, Encoding};
+use crate::{validation::is_chat_safe, NcrError};
+
+/// Alphabets up to this size are decoded with a linear scan, which is fast enough and avoids a 128 KiB table.
+const SCAN_LEN: usize = 64;
+
+/// A user-supplied alphabet for [AlphabetEncoding].
+pub trait Alphabet {
+    /// The characters of the alphabet, the index of a character is its value.
+    ///
+    /// Must pass [validate_alphabet].
+    const CHARS: &'static [char];
+}
+
+/// An encoding using any alphabet.
+///
+/// If the size of the alphabet is a power of two up to 256, the bits of the input are packed into characters (most significant bit first),
+/// and the last character is padded with zero bits.
+/// This is compatible with [Sus16Encoding](super::Sus16Encoding) and [Mc256Encoding](super::Mc256Encoding) given the same alphabet.
+///
+/// Otherwise, the input is treated as a big-endian number and converted to the radix of the alphabet,
+/// with every leading zero byte encoded as the first character (like base58).
+/// This is quadratic in the length of the input, which is fine for chat messages.
+///
+/// The alphabet is checked with [validate_alphabet] at compile time.
+///
+/// # Examples
+///
+/// ```
+/// use ncr::encoding::{Alphabet, AlphabetEncoding, Encoding, Sus16Encoding};
+///
+/// struct Sus16;
+///
+/// impl Alphabet for Sus16 {
+///     const CHARS: &'static [char] = &[
+///         'ඔ', 'ඕ', 'ඖ', 'ඞ', 'ච', 'ඩ', 'ඬ', 'ධ', 'ඹ', 'ව', 'ဨ', '၅', '၆', '၉', 'ၡ', 'ဥ',
+///     ];
+/// }
+///
+/// let encoded = AlphabetEncoding::<Sus16>::encode(b"#%Hello, world!");
+/// assert_eq!(encoded, Sus16Encoding::encode(b"#%Hello, world!"));
+///
+/// struct Digits;
+///
+/// impl Alphabet for Digits {
+///     const CHARS: &'static [char] = &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+/// }
+///
+/// let encoded = AlphabetEncoding::<Digits>::encode(&[0, 1, 0]);
+/// assert_eq!(encoded, "0256");
+/// assert_eq!(AlphabetEncoding::<Digits>::decode(&encoded).unwrap(), [0, 1, 0]);
+///
+/// // 1024 CJK ideographs, decoded with a table built at compile time.
+/// struct Cjk;
+///
+/// impl Alphabet for Cjk {
+///     const CHARS: &'static [char] = &{
+///         let mut chars = ['\0'; 1024];
+///         let mut i = 0;
+///         while i < chars.len() {
+///             chars[i] = match char::from_u32(0x4E00 + i as u32) {
+///                 Some(ch) => ch,
+///                 None => panic!(),
+///             };
+///             i += 1;
+///         }
+///         chars
+///     };
+/// }
+///
+/// for len in 0..32 {
+///     let data = vec![0xAB; len];
+///     let encoded = AlphabetEncoding::<Cjk>::encode(&data);
+///     assert_eq!(AlphabetEncoding::<Cjk>::decode(&encoded).unwrap(), data);
+/// }
+/// ```
+///
+/// An invalid alphabet fails to compile:
+///
+/// ```compile_fail
+/// use ncr::encoding::{Alphabet, AlphabetEncoding, Encoding};
+///
+/// struct Invalid;
+///
+/// impl Alphabet for Invalid {
+///     const CHARS: &'static [char] = &['a', 'b', '§'];
+/// }
+///
+/// AlphabetEncoding::<Invalid>::encode(b"Hello");
+/// ```
+#[derive(Debug)]
+pub struct AlphabetEncoding<A: Alphabet>(PhantomData<A>);
+
+impl<A: Alphabet> AlphabetEncoding<A> {
+    const VALID: () = match validate_alphabet(A::CHARS) {
+        Ok(()) => (),
+        Err(_) => panic!("invalid alphabet"),
+    };
+
+    /// Number of bits per character, if the size of the alphabet is a power of two up to 256.
+    ///
+    /// With more than 8 bits per character, the padding can be a whole byte, so the length of the input would be ambiguous.
+    const BITS: Option<u32> = if A::CHARS.len().is_power_of_two() && A::CHARS.len() <= 256 {
+        Some(A::CHARS.len().trailing_zeros())
+    } else {
+        None
+    };
+
+    /// Table for decoding large alphabets in the basic multilingual plane, built at compile time.
+    ///
+    /// Other alphabets are decoded with a linear scan.
+    const TABLE: Option<&'static DecodeTable<256>> =
+        if A::CHARS.len() > SCAN_LEN && is_bmp(A::CHARS) {
+            Some(&DecodeTable::new(A::CHARS))
+        } else {
+            None
+        };
+
+    #[inline]
+    fn decode_char(ch: char) -> Result<u32, NcrError> {
+        let value = match Self::TABLE {
+            Some(table) => table.get(ch).map(u32::from),
+            None => A::CHARS.iter().position(|c| *c == ch).map(|i| i as u32),
+        };
+
+        value.ok_or(NcrError::DecodeError)
+    }
+}
+
+impl<A: Alphabet> Encoding for AlphabetEncoding<A> {
+    fn encode(text: &[u8]) -> String {
+        let mut output = String::new();
+        Self::encode_into(text, &mut output);
+
+        output
+    }
+
+    fn decode(text: &str) -> Result<Vec<u8>, NcrError> {
+        let mut output = Vec::new();
+        Self::decode_into(text, &mut output)?;
+
+        Ok(output)
+    }
+
+    fn encode_into(text: &[u8], output: &mut String) {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID;
+
+        match Self::BITS {
+            Some(bits) => encode_bits(text, output, A::CHARS, bits),
+            None => encode_radix(text, output, A::CHARS),
+        }
+    }
+
+    fn decode_into(text: &str, output: &mut Vec<u8>) -> Result<(), NcrError> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID;
+
+        let values = text.chars().map(Self::decode_char);
+
+        match Self::BITS {
+            Some(bits) => decode_bits(values, output, bits),
+            None => decode_radix(values, output, A::CHARS.len() as u32),
+        }
+    }
+}
+
+fn encode_bits(text: &[u8], output: &mut String, alphabet: &[char], bits: u32) {
+    let mut buffer = 0u32;
+    let mut len = 0;
+
+    for byte in text {
+        buffer = (buffer << 8) | *byte as u32;
+        len += 8;
+
+        while len >= bits {
+            len -= bits;
+            output.push(alphabet[(buffer >> len) as usize]);
+            buffer &= (1 << len) - 1;
+        }
+    }
+
+    if len != 0 {
+        output.push(alphabet[(buffer << (bits - len)) as usize]);
+    }
+}
+
+fn decode_bits(
+    values: impl Iterator<Item = Result<u32, NcrError>>,
+    output: &mut Vec<u8>,
+    bits: u32,
+) -> Result<(), NcrError> {
+    let mut buffer = 0u32;
+    let mut len = 0;
+
+    for value in values {
+        buffer = (buffer << bits) | value?;
+        len += bits;
+
+        while len >= 8 {
+            len -= 8;
+            output.push((buffer >> len) as u8);
+            buffer &= (1 << len) - 1;
+        }
+    }
+
+    // The remaining bits are padding, they must be zeros and less than a character.
+    if buffer != 0 || len >= bits {
+        return Err(NcrError::DecodeError);
+    }
+
+    Ok(())
+}
+
+fn encode_radix(text: &[u8], output: &mut String, alphabet: &[char]) {
+    let radix = alphabet.len() as u64;
+
+    let zeros = text.iter().take_while(|byte| **byte == 0).count();
+
+    // Digits in little-endian order.
+    let mut digits: Vec<u32> = Vec::new();
+    for byte in &text[zeros..] {
+        let mut carry = *byte as u64;
+
+        for digit in digits.iter_mut() {
+            carry += (*digit as u64) << 8;
+            *digit = (carry % radix) as u32;
+            carry /= radix;
+        }
+        while carry != 0 {
+            digits.push((carry % radix) as u32);
+            carry /= radix;
+        }
+    }
+
+    output.extend(std::iter::repeat_n(alphabet[0], zeros));
+    output.extend(digits.iter().rev().map(|digit| alphabet[*digit as usize]));
+}
+
+fn decode_radix(
+    values: impl Iterator<Item = Result<u32, NcrError>>,
+    output: &mut Vec<u8>,
+    radix: u32,
+) -> Result<(), NcrError> {
+    let mut values = values.peekable();
+
+    let mut zeros = 0;
+    while let Some(Ok(0)) = values.peek() {
+        values.next();
+        zeros += 1;
+    }
+
+    // Bytes in little-endian order.
+    let mut bytes: Vec<u8> = Vec::new();
+    for value in values {
+        let mut carry = value? as u64;
+
+        for byte in bytes.iter_mut() {
+            carry += *byte as u64 * radix as u64;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry != 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    output.resize(output.len() + zeros, 0);
+    output.extend(bytes.iter().rev());
+
+    Ok(())
+}
+
+/// Check that an alphabet can be used with [AlphabetEncoding].
+///
+/// An alphabet must:
+/// - Have between 2 and 65536 characters.
+/// - Not contain the same character twice.
+/// - Only contain characters that pass [is_chat_safe]: no `§` (formatting codes), DEL, control characters or whitespace,
+///   which Minecraft chat rejects or alters.
+/// - Not contain `/`, because any character can start a message, and a message starting with `/` is sent as a command.
+///
+/// # Examples
+///
+/// ```
+/// use ncr::encoding::{validate_alphabet, AlphabetError};
+///
+/// assert_eq!(validate_alphabet(&['a', 'b', 'c']), Ok(()));
+/// assert_eq!(validate_alphabet(&['a', 'b', 'a']), Err(AlphabetError::Duplicate('a')));
+/// assert_eq!(validate_alphabet(&['a', '/']), Err(AlphabetError::Forbidden('/')));
+/// ```
+pub const fn validate_alphabet(chars: &[char]) -> Result<(), AlphabetError> {
+    if chars.len() < 2 {
+        return Err(AlphabetError::TooShort);
+    }
+    if chars.len() > 1 << 16 {
+        return Err(AlphabetError::TooLong);
+    }
+
+    // One bit per code point, so large alphabets don't take quadratic time at compile time.
+    let mut seen = [0u64; 0x110000 / 64];
+
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+
+        if ch == '/' || !is_chat_safe(ch) {
+            return Err(AlphabetError::Forbidden(ch));
+        }
+
+        let (word, bit) = (ch as usize / 64, 1 << (ch as u32 % 64));
+        if seen[word] & bit != 0 {
+            return Err(AlphabetError::Duplicate(ch));
+        }
+        seen[word] |= bit;
+
+        i += 1;
+    }
+
+    Ok(())
+}
+
+const fn is_bmp(chars: &[char]) -> bool {
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] as u32 > 0xFFFF {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+/// This represents all errors that can happen when validating an alphabet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlphabetError {
+    TooShort,
+    TooLong,
+    Duplicate(char),
+    Forbidden(char),
+}
+
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlphabetError::TooShort => write!(f, "Alphabet too short"),
+            AlphabetError::TooLong => write!(f, "Alphabet too long"),
+            AlphabetError::Duplicate(ch) => write!(f, "Duplicate character {ch:?} in alphabet"),
+            AlphabetError::Forbidden(ch) => write!(f, "Forbidden character {ch:?} in alphabet"),
+        }
+    }
+}
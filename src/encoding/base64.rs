@@ -26,6 +26,16 @@ impl Encoding for Base64Encoding {
             .decode(text)
             .map_err(|_| NcrError::DecodeError)
     }
+
+    fn encode_into(text: &[u8], output: &mut String) {
+        BASE64_ENGINE.encode_string(text, output)
+    }
+
+    fn decode_into(text: &str, output: &mut Vec<u8>) -> Result<(), NcrError> {
+        BASE64_ENGINE
+            .decode_vec(text, output)
+            .map_err(|_| NcrError::DecodeError)
+    }
 }
 
 const BASE64_ALPHABET: Alphabet =
@@ -1,6 +1,4 @@
-use base64::{engine::general_purpose::STANDARD, Engine};
-
-use super::Encoding;
+use super::{table::DecodeTable, Encoding};
 use crate::NcrError;
 
 /// The base64r encoding, made by [No Chat Reports](https://github.com/HKS-HNS/No-Chat-Reports).
@@ -12,24 +10,25 @@ pub struct Base64rEncoding;
 
 impl Encoding for Base64rEncoding {
     fn encode(text: &[u8]) -> String {
-        let encoded = STANDARD.encode(text);
         let mut output = String::new();
-
-        for ch in encoded.chars() {
-            output.push(unsafe { *BASE64R_ENCODE.get(&ch).unwrap_unchecked() });
-        }
+        Self::encode_into(text, &mut output);
 
         output
     }
 
     fn decode(text: &str) -> Result<Vec<u8>, NcrError> {
-        let mut output = String::new();
+        let mut output = Vec::new();
+        Self::decode_into(text, &mut output)?;
 
-        for ch in text.chars() {
-            output.push(*BASE64R_DECODE.get(&ch).ok_or(NcrError::DecodeError)?);
-        }
+        Ok(output)
+    }
+
+    fn encode_into(text: &[u8], output: &mut String) {
+        encode_base64r(text, output, &BASE64R_ENCODE)
+    }
 
-        STANDARD.decode(output).map_err(|_| NcrError::DecodeError)
+    fn decode_into(text: &str, output: &mut Vec<u8>) -> Result<(), NcrError> {
+        decode_base64r(text, output, &BASE64R_DECODE)
     }
 }
 
@@ -42,140 +41,139 @@ pub struct NewBase64rEncoding;
 
 impl Encoding for NewBase64rEncoding {
     fn encode(text: &[u8]) -> String {
-        let encoded = STANDARD.encode(text);
         let mut output = String::new();
-
-        for ch in encoded.chars() {
-            let new_ch = if ch == 'x' {
-                '×'
-            } else {
-                unsafe { *BASE64R_ENCODE.get(&ch).unwrap_unchecked() }
-            };
-
-            output.push(new_ch);
-        }
+        Self::encode_into(text, &mut output);
 
         output
     }
 
     fn decode(text: &str) -> Result<Vec<u8>, NcrError> {
-        let mut output = String::new();
+        let mut output = Vec::new();
+        Self::decode_into(text, &mut output)?;
+
+        Ok(output)
+    }
+
+    fn encode_into(text: &[u8], output: &mut String) {
+        encode_base64r(text, output, &NEW_BASE64R_ENCODE)
+    }
+
+    fn decode_into(text: &str, output: &mut Vec<u8>) -> Result<(), NcrError> {
+        decode_base64r(text, output, &NEW_BASE64R_DECODE)
+    }
+}
 
-        for ch in text.chars() {
-            let new_ch = if ch == '×' {
-                'x'
-            } else {
-                *BASE64R_DECODE.get(&ch).ok_or(NcrError::DecodeError)?
-            };
+// Base64r is the standard base64 (with padding), except every character is replaced.
+// Both encoding and decoding are done in a single pass, without going through an intermediate base64 string.
 
-            output.push(new_ch);
+/// Value of the padding character in the decode tables.
+const PAD: u16 = 64;
+
+fn encode_base64r(text: &[u8], output: &mut String, alphabet: &[char; 65]) {
+    output.reserve(text.len().div_ceil(3) * 8);
+
+    let mut chunks = text.chunks_exact(3);
+    for chunk in &mut chunks {
+        let n = (chunk[0] as usize) << 16 | (chunk[1] as usize) << 8 | chunk[2] as usize;
+
+        output.push(alphabet[n >> 18]);
+        output.push(alphabet[(n >> 12) & 0x3F]);
+        output.push(alphabet[(n >> 6) & 0x3F]);
+        output.push(alphabet[n & 0x3F]);
+    }
+
+    match *chunks.remainder() {
+        [a] => {
+            let n = (a as usize) << 16;
+
+            output.push(alphabet[n >> 18]);
+            output.push(alphabet[(n >> 12) & 0x3F]);
+            output.push(alphabet[PAD as usize]);
+            output.push(alphabet[PAD as usize]);
         }
+        [a, b] => {
+            let n = (a as usize) << 16 | (b as usize) << 8;
 
-        STANDARD.decode(output).map_err(|_| NcrError::DecodeError)
+            output.push(alphabet[n >> 18]);
+            output.push(alphabet[(n >> 12) & 0x3F]);
+            output.push(alphabet[(n >> 6) & 0x3F]);
+            output.push(alphabet[PAD as usize]);
+        }
+        _ => {}
     }
 }
 
-// Post-expanded macros:
+fn decode_base64r(
+    text: &str,
+    output: &mut Vec<u8>,
+    table: &DecodeTable<1>,
+) -> Result<(), NcrError> {
+    output.reserve(text.len() / 4 * 3);
+
+    let mut quad = [0u16; 4];
+    let mut len = 0;
+    let mut finished = false;
+
+    for ch in text.chars() {
+        // Padding is only allowed at the end.
+        if finished {
+            return Err(NcrError::DecodeError);
+        }
 
-#[rustfmt::skip]
-const BASE64R_ENCODE: phf::Map<char, char> = phf::Map {
-    key: 15467950696543387533u64,
-    disps: &[
-        (6u32, 14u32), (3u32, 2u32), (0u32, 0u32), (6u32, 6u32),
-        (3u32, 30u32), (1u32, 47u32), (0u32, 3u32), (17u32, 11u32),
-        (4u32, 64u32), (0u32, 1u32), (0u32, 0u32), (0u32, 13u32),
-        (0u32, 8u32),
-    ],
-    entries: &[
-        ('V', ']'), ('Q', '>'), ('K', '-'), ('p', '¯'),
-        ('t', '³'), ('8', '8'), ('r', '±'), ('u', 'µ'),
-        ('5', '5'), ('d', '¡'), ('i', '¦'), ('c', '~'),
-        ('6', '6'), ('S', '@'), ('=', '¿'), ('3', '3'),
-        ('a', '|'), ('U', '\\'), ('R', '?'), ('+', '+'),
-        ('D', '$'), ('q', '°'), ('W', '^'), ('9', '9'),
-        ('e', '¢'), ('E', '%'), ('N', ';'), ('Z', '{'),
-        ('P', '='), ('T', '['), ('v', '¶'), ('A', '!'),
-        ('H', '('), ('z', 'º'), ('s', '²'), ('k', '©'),
-        ('O', '<'), ('h', '¥'), ('f', '£'), ('F', '¼'),
-        ('o', '®'), ('4', '4'), ('M', ':'), ('0', '0'),
-        ('2', '2'), ('1', '1'), ('j', '¨'), ('l', 'ª'),
-        ('L', '.'), ('Y', '`'), ('J', ','), ('w', '·'),
-        ('b', '}'), ('g', '¤'), ('7', '7'), ('B', '"'),
-        ('G', '\''), ('x', '¸'), ('y', '¹'), ('/', '»'),
-        ('X', '_'), ('I', ')'), ('m', '«'), ('C', '#'),
-        ('n', '¬'),
-    ],
-};
+        quad[len] = table.get(ch).ok_or(NcrError::DecodeError)?;
+        len += 1;
 
+        if len < 4 {
+            continue;
+        }
+        len = 0;
+
+        let n = (quad[0] as u32) << 18 | (quad[1] as u32) << 12 | (quad[2] as u32) << 6;
+
+        match quad {
+            [a, b, c, d] if a < PAD && b < PAD && c < PAD && d < PAD => {
+                let n = n | d as u32;
+                output.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8, n as u8]);
+            }
+            // Unused bits must be zero, like the standard base64 engine.
+            [a, b, c, PAD] if a < PAD && b < PAD && c < PAD && c & 0x3 == 0 => {
+                output.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8]);
+                finished = true;
+            }
+            [a, b, PAD, PAD] if a < PAD && b < PAD && b & 0xF == 0 => {
+                output.push((n >> 16) as u8);
+                finished = true;
+            }
+            _ => return Err(NcrError::DecodeError),
+        }
+    }
+
+    if len != 0 {
+        return Err(NcrError::DecodeError);
+    }
+
+    Ok(())
+}
+
+/// The base64r alphabet, in the order of the standard base64 alphabet, followed by the padding character.
 #[rustfmt::skip]
-const BASE64R_DECODE: phf::Map<char, char> = phf::Map {
-    key: 15467950696543387533u64,
-    disps: &[
-        (11u32, 49u32), (0u32, 64u32), (0u32, 0u32), (0u32, 20u32),
-        (7u32, 44u32), (6u32, 54u32), (10u32, 25u32), (0u32, 1u32),
-        (41u32, 56u32), (1u32, 0u32), (4u32, 35u32), (1u32, 0u32),
-        (45u32, 42u32),
-    ],
-    entries: &[
-        ('·', 'w'), ('¤', 'g'), ('\'', 'G'), ('¸', 'x'),
-        ('«', 'm'), ('^', 'W'), ('¥', 'h'), ('`', 'Y'),
-        ('2', '2'), ('¢', 'e'), ('\\', 'U'), ('7', '7'),
-        ('(', 'H'), (';', 'N'), ('=', 'P'), ('£', 'f'),
-        ('°', 'q'), ('µ', 'u'), ('<', 'O'), ('¡', 'd'),
-        ('¨', 'j'), ('3', '3'), ('¶', 'v'), ('~', 'c'),
-        ('¬', 'n'), ('@', 'S'), ('©', 'k'), ('¯', 'p'),
-        ('9', '9'), ('?', 'R'), ('{', 'Z'), ('|', 'a'),
-        ('ª', 'l'), ('-', 'K'), ('_', 'X'), ('¦', 'i'),
-        ('$', 'D'), ('4', '4'), ('³', 't'), ('[', 'T'),
-        ('8', '8'), ('#', 'C'), ('"', 'B'), ('²', 's'),
-        ('+', '+'), (',', 'J'), ('.', 'L'), (')', 'I'),
-        ('®', 'o'), ('º', 'z'), ('6', '6'), (']', 'V'),
-        ('»', '/'), ('1', '1'), ('0', '0'), ('±', 'r'),
-        ('!', 'A'), ('>', 'Q'), ('¼', 'F'), ('¿', '='),
-        (':', 'M'), ('5', '5'), ('%', 'E'), ('}', 'b'),
-        ('¹', 'y'),
-    ],
+const BASE64R_ENCODE: [char; 65] = [
+    '!', '"', '#', '$', '%', '¼', '\'', '(', ')', ',', '-', '.', ':', ';', '<', '=',
+    '>', '?', '@', '[', '\\', ']', '^', '_', '`', '{', '|', '}', '~', '¡', '¢', '£',
+    '¤', '¥', '¦', '¨', '©', 'ª', '«', '¬', '®', '¯', '°', '±', '²', '³', 'µ', '¶',
+    '·', '¸', '¹', 'º', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '+', '»',
+    '¿',
+];
+
+/// Same as [BASE64R_ENCODE], except `¸` is replaced by `×`.
+const NEW_BASE64R_ENCODE: [char; 65] = {
+    let mut alphabet = BASE64R_ENCODE;
+    alphabet[49] = '×';
+    alphabet
 };
 
-// Pre-expanded macros:
-// phf = { version = "0.11.1", features = ["macros"] }
-
-// const BASE64R_ENCODE: phf::Map<char, char> = phf::phf_map! {
-//     'A' => '!', 'B' => '"', 'C' => '#', 'D' => '$',
-//     'E' => '%', 'F' => '¼', 'G' => '\'', 'H' => '(',
-//     'I' => ')', 'J' => ',', 'K' => '-', 'L' => '.',
-//     'M' => ':', 'N' => ';', 'O' => '<', 'P' => '=',
-//     'Q' => '>', 'R' => '?', 'S' => '@', 'T' => '[',
-//     'U' => '\\', 'V' => ']', 'W' => '^', 'X' => '_',
-//     'Y' => '`', 'Z' => '{', 'a' => '|', 'b' => '}',
-//     'c' => '~', 'd' => '¡', 'e' => '¢', 'f' => '£',
-//     'g' => '¤', 'h' => '¥', 'i' => '¦', 'j' => '¨',
-//     'k' => '©', 'l' => 'ª', 'm' => '«', 'n' => '¬',
-//     'o' => '®', 'p' => '¯', 'q' => '°', 'r' => '±',
-//     's' => '²', 't' => '³', 'u' => 'µ', 'v' => '¶',
-//     'w' => '·', 'x' => '¸', 'y' => '¹', 'z' => 'º',
-//     '0' => '0', '1' => '1', '2' => '2', '3' => '3',
-//     '4' => '4', '5' => '5', '6' => '6', '7' => '7',
-//     '8' => '8', '9' => '9', '+' => '+', '/' => '»',
-//     '=' => '¿',
-// };
-//
-// const BASE64R_DECODE: phf::Map<char, char> = phf::phf_map! {
-//     '!' => 'A', '"' => 'B', '#' => 'C', '$' => 'D',
-//     '%' => 'E', '¼' => 'F', '\'' => 'G', '(' => 'H',
-//     ')' => 'I', ',' => 'J', '-' => 'K', '.' => 'L',
-//     ':' => 'M', ';' => 'N', '<' => 'O', '=' => 'P',
-//     '>' => 'Q', '?' => 'R', '@' => 'S', '[' => 'T',
-//     '\\' => 'U', ']' => 'V', '^' => 'W', '_' => 'X',
-//     '`' => 'Y', '{' => 'Z', '|' => 'a', '}' => 'b',
-//     '~' => 'c', '¡' => 'd', '¢' => 'e', '£' => 'f',
-//     '¤' => 'g', '¥' => 'h', '¦' => 'i', '¨' => 'j',
-//     '©' => 'k', 'ª' => 'l', '«' => 'm', '¬' => 'n',
-//     '®' => 'o', '¯' => 'p', '°' => 'q', '±' => 'r',
-//     '²' => 's', '³' => 't', 'µ' => 'u', '¶' => 'v',
-//     '·' => 'w', '¸' => 'x', '¹' => 'y', 'º' => 'z',
-//     '0' => '0', '1' => '1', '2' => '2', '3' => '3',
-//     '4' => '4', '5' => '5', '6' => '6', '7' => '7',
-//     '8' => '8', '9' => '9', '+' => '+', '»' => '/',
-//     '¿' => '=',
-// };
+// Fails to compile if a character appears twice.
+const BASE64R_DECODE: DecodeTable<1> = DecodeTable::new(&BASE64R_ENCODE);
+
+/// Accepts both `¸` and `×`.
+const NEW_BASE64R_DECODE: DecodeTable<1> = BASE64R_DECODE.with('×', 49);
@@ -0,0 +1,44 @@
+use std::marker::PhantomData;
+
+use super::Encoding;
+use crate::{utils::strip_formatting, NcrError};
+
+/// Lenient decoding for any encoding.
+///
+/// Before decoding, formatting codes and other decoration inserted by servers are removed with [strip_formatting].
+/// Encoding is unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use ncr::encoding::{Base64rEncoding, Encoding, Lenient};
+///
+/// let encoded = Base64rEncoding::encode(b"#%Hello, world!");
+/// let decorated = format!("§7{}§r", encoded);
+///
+/// assert!(Base64rEncoding::decode(&decorated).is_err());
+/// assert_eq!(
+///     Lenient::<Base64rEncoding>::decode(&decorated).unwrap(),
+///     b"#%Hello, world!"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct Lenient<E: Encoding>(PhantomData<E>);
+
+impl<E: Encoding> Encoding for Lenient<E> {
+    fn encode(text: &[u8]) -> String {
+        E::encode(text)
+    }
+
+    fn decode(text: &str) -> Result<Vec<u8>, NcrError> {
+        E::decode(&strip_formatting(text))
+    }
+
+    fn encode_into(text: &[u8], output: &mut String) {
+        E::encode_into(text, output)
+    }
+
+    fn decode_into(text: &str, output: &mut Vec<u8>) -> Result<(), NcrError> {
+        E::decode_into(&strip_formatting(text), output)
+    }
+}
@@ -0,0 +1,174 @@
+use super::Encoding;
+use crate::NcrError;
+
+/// The mc32768 encoding, a high density encoding where every character carries 15 bits.
+///
+/// A 256 character chat message fits 480 bytes, compared to 256 bytes with [Mc256Encoding](super::Mc256Encoding)
+/// and 128 bytes with [Sus16Encoding](super::Sus16Encoding).
+///
+/// # Alphabet
+///
+/// All characters are in the basic multilingual plane, so each one counts as a single character towards the chat length limit.
+/// They are rendered by the vanilla font (through its unifont fallback) and accepted by chat
+/// (no `§`, no DEL, no control characters and no whitespace).
+///
+/// - `U+3400` to `U+9FFF` (CJK Unified Ideographs Extension A, Yijing Hexagram Symbols, CJK Unified Ideographs):
+///   values 0 to 27647.
+/// - `U+AC00` to `U+BFFF` (Hangul Syllables): values 27648 to 32767.
+/// - `U+C000` to `U+C07F` (Hangul Syllables): used only for the last character, when it carries 7 bits or less.
+///
+/// The bits of the last character that aren't part of the data are set to 1, like [base32768](https://github.com/qntm/base32768).
+///
+/// # Examples
+///
+/// ```
+/// use ncr::encoding::{Encoding, Mc32768Encoding};
+///
+/// let data: Vec<u8> = (0..=255).collect();
+/// let encoded = Mc32768Encoding::encode(&data);
+///
+/// // 256 bytes fit in 137 characters.
+/// assert_eq!(encoded.chars().count(), 137);
+/// assert_eq!(Mc32768Encoding::decode(&encoded).unwrap(), data);
+///
+/// // A short last character holding only padding is rejected, so every input has a single encoding.
+/// assert!(Mc32768Encoding::decode("\u{C07F}").is_err());
+/// let fifteen = Mc32768Encoding::encode(&[0u8; 15]);
+/// assert_eq!(fifteen.chars().count(), 8);
+/// assert!(Mc32768Encoding::decode(&format!("{fifteen}\u{C07F}")).is_err());
+///
+/// // Every 15 bit value, and every 7 bit value of the last character.
+/// let mut alphabet: Vec<char> = (0x3400..=0x9FFF).chain(0xAC00..=0xC07F).map(|ch| char::from_u32(ch).unwrap()).collect();
+/// assert_eq!(alphabet.len(), 32768 + 128);
+///
+/// for ch in alphabet {
+///     assert!(ch != '§' && ch != '\u{7F}' && !ch.is_control() && !ch.is_whitespace());
+///     assert!(ch.len_utf16() == 1);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Mc32768Encoding;
+
+const FULL_BITS: u32 = 15;
+const SHORT_BITS: u32 = 7;
+
+/// Number of values in the first range.
+const FIRST_LEN: u32 = 0xA000 - 0x3400;
+const FIRST_START: u32 = 0x3400;
+const SECOND_START: u32 = 0xAC00;
+const SHORT_START: u32 = 0xC000;
+
+#[inline]
+fn encode_full(value: u32) -> char {
+    let code = if value < FIRST_LEN {
+        FIRST_START + value
+    } else {
+        SECOND_START + value - FIRST_LEN
+    };
+
+    // The ranges only contain valid characters.
+    unsafe { char::from_u32_unchecked(code) }
+}
+
+#[inline]
+fn encode_short(value: u32) -> char {
+    unsafe { char::from_u32_unchecked(SHORT_START + value) }
+}
+
+/// Returns the value and the number of bits of a character.
+#[inline]
+fn decode_char(ch: char) -> Result<(u32, u32), NcrError> {
+    match ch as u32 {
+        code @ 0x3400..=0x9FFF => Ok((code - FIRST_START, FULL_BITS)),
+        code @ 0xAC00..=0xBFFF => Ok((code - SECOND_START + FIRST_LEN, FULL_BITS)),
+        code @ 0xC000..=0xC07F => Ok((code - SHORT_START, SHORT_BITS)),
+        _ => Err(NcrError::DecodeError),
+    }
+}
+
+impl Encoding for Mc32768Encoding {
+    fn encode(text: &[u8]) -> String {
+        let mut output = String::new();
+        Self::encode_into(text, &mut output);
+
+        output
+    }
+
+    fn decode(text: &str) -> Result<Vec<u8>, NcrError> {
+        let mut output = Vec::new();
+        Self::decode_into(text, &mut output)?;
+
+        Ok(output)
+    }
+
+    fn encode_into(text: &[u8], output: &mut String) {
+        // Every character is 3 bytes long in utf-8.
+        output.reserve((text.len() * 8).div_ceil(FULL_BITS as usize) * 3);
+
+        let mut buffer = 0u32;
+        let mut bits = 0;
+
+        for byte in text {
+            buffer = (buffer << 8) | *byte as u32;
+            bits += 8;
+
+            if bits >= FULL_BITS {
+                bits -= FULL_BITS;
+                output.push(encode_full(buffer >> bits));
+                buffer &= (1 << bits) - 1;
+            }
+        }
+
+        if bits == 0 {
+            return;
+        }
+
+        // Pad the remaining bits with ones.
+        if bits <= SHORT_BITS {
+            let padding = SHORT_BITS - bits;
+            output.push(encode_short((buffer << padding) | ((1 << padding) - 1)));
+        } else {
+            let padding = FULL_BITS - bits;
+            output.push(encode_full((buffer << padding) | ((1 << padding) - 1)));
+        }
+    }
+
+    fn decode_into(text: &str, output: &mut Vec<u8>) -> Result<(), NcrError> {
+        output.reserve(text.len() / 3 * FULL_BITS as usize / 8);
+
+        let mut buffer = 0u32;
+        let mut bits = 0;
+        let mut finished = false;
+
+        for ch in text.chars() {
+            // A short character can only be the last one.
+            if finished {
+                return Err(NcrError::DecodeError);
+            }
+
+            let (value, len) = decode_char(ch)?;
+            finished = len == SHORT_BITS;
+
+            // A short character must carry data, the encoder never emits one that is only padding.
+            if finished && bits == 0 {
+                return Err(NcrError::DecodeError);
+            }
+
+            buffer = (buffer << len) | value;
+            bits += len;
+
+            while bits >= 8 {
+                bits -= 8;
+                output.push((buffer >> bits) as u8);
+                buffer &= (1 << bits) - 1;
+            }
+        }
+
+        // The remaining bits are padding, and must be ones.
+        if buffer != (1 << bits) - 1 {
+            return Err(NcrError::DecodeError);
+        }
+
+        Ok(())
+    }
+}
@@ -24,17 +24,43 @@
 //! assert_eq!(String::from_utf8(decoded).unwrap(), "#%Hello, world!");
 //! ```
 //!
+//! ## Reusing buffers
+//!
+//! ```
+//! use ncr::encoding::{Base64rEncoding, Encoding};
+//!
+//! let mut encoded = String::new();
+//! let mut decoded = Vec::new();
+//!
+//! for message in ["#%Hello", "#%world!"] {
+//!     encoded.clear();
+//!     decoded.clear();
+//!
+//!     Base64rEncoding::encode_into(message.as_bytes(), &mut encoded);
+//!     Base64rEncoding::decode_into(&encoded, &mut decoded).unwrap();
+//!
+//!     assert_eq!(decoded, message.as_bytes());
+//! }
+//! ```
+//!
 
+mod alphabet;
 mod base64;
 mod base64r;
+mod lenient;
 mod mc256;
+mod mc32768;
 mod sus16;
+mod table;
 
 use crate::NcrError;
 
 pub use self::base64::Base64Encoding;
+pub use alphabet::{validate_alphabet, Alphabet, AlphabetEncoding, AlphabetError};
 pub use base64r::{Base64rEncoding, NewBase64rEncoding};
+pub use lenient::Lenient;
 pub use mc256::Mc256Encoding;
+pub use mc32768::Mc32768Encoding;
 pub use sus16::Sus16Encoding;
 
 /// The encoding trait.
@@ -44,4 +70,23 @@ pub trait Encoding {
 
     /// Decode a given text.
     fn decode(text: &str) -> Result<Vec<u8>, NcrError>;
+
+    /// Encode a given text, appending the result to `output`.
+    ///
+    /// This allows reusing the same buffer across many messages.
+    fn encode_into(text: &[u8], output: &mut String) {
+        output.push_str(&Self::encode(text));
+    }
+
+    /// Decode a given text, appending the result to `output`.
+    ///
+    /// This allows reusing the same buffer across many messages.
+    ///
+    /// # Error
+    ///
+    /// On error, `output` may contain partially decoded bytes.
+    fn decode_into(text: &str, output: &mut Vec<u8>) -> Result<(), NcrError> {
+        output.extend_from_slice(&Self::decode(text)?);
+        Ok(())
+    }
 }
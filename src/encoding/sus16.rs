@@ -1,4 +1,4 @@
-use super::Encoding;
+use super::{table::DecodeTable, Encoding};
 use crate::NcrError;
 
 /// The sus16 encoding, made by [EnderKill98](https://github.com/EnderKill98).
@@ -10,21 +10,33 @@ pub struct Sus16Encoding;
 impl Encoding for Sus16Encoding {
     fn encode(text: &[u8]) -> String {
         let mut output = String::new();
+        Self::encode_into(text, &mut output);
+
+        output
+    }
+
+    fn decode(text: &str) -> Result<Vec<u8>, NcrError> {
+        let mut output = Vec::new();
+        Self::decode_into(text, &mut output)?;
+
+        Ok(output)
+    }
+
+    fn encode_into(text: &[u8], output: &mut String) {
+        // Every character is 3 bytes long in utf-8.
+        output.reserve(text.len() * 6);
 
         for ch in text {
             output.push(SUS16_ENCODE[(*ch >> 4) as usize]);
             output.push(SUS16_ENCODE[(*ch & 0xf) as usize]);
         }
-
-        output
     }
 
-    fn decode(text: &str) -> Result<Vec<u8>, NcrError> {
-        let mut output: Vec<u8> = Vec::new();
+    fn decode_into(text: &str, output: &mut Vec<u8>) -> Result<(), NcrError> {
+        output.reserve(text.len() / 6);
 
         let mut chars = text.chars();
-        loop {
-            let Some(c1) = chars.next() else { break; };
+        while let Some(c1) = chars.next() {
             let c2 = chars.next().ok_or(NcrError::DecodeError)?;
 
             let r1 = sus16_decode(c1)?;
@@ -33,7 +45,7 @@ impl Encoding for Sus16Encoding {
             output.push((r1 << 4) | r2);
         }
 
-        Ok(output)
+        Ok(())
     }
 }
 
@@ -41,13 +53,13 @@ const SUS16_ENCODE: [char; 16] = [
     'ඔ', 'ඕ', 'ඖ', 'ඞ', 'ච', 'ඩ', 'ඬ', 'ධ', 'ඹ', 'ව', 'ဨ', '၅', '၆', '၉', 'ၡ', 'ဥ',
 ];
 
-#[rustfmt::skip]
+// Fails to compile if a character appears twice.
+const SUS16_DECODE: DecodeTable<2> = DecodeTable::new(&SUS16_ENCODE);
+
+#[inline]
 fn sus16_decode(ch: char) -> Result<u8, NcrError> {
-    Ok(match ch {
-        'ඔ' => 0, 'ඕ' => 1, 'ඖ' => 2, 'ඞ' => 3,
-        'ච' => 4, 'ඩ' => 5, 'ඬ' => 6, 'ධ' => 7,
-        'ඹ' => 8, 'ව' => 9, 'ဨ' => 10, '၅' => 11,
-        '၆' => 12, '၉' => 13, 'ၡ' => 14, 'ဥ' => 15,
-        _ => return Err(NcrError::DecodeError),
-    })
+    SUS16_DECODE
+        .get(ch)
+        .map(|value| value as u8)
+        .ok_or(NcrError::DecodeError)
 }
@@ -0,0 +1,109 @@
+// Lookup tables for the table-driven encodings, generated at compile time from the encode alphabets.
+
+/// Maps characters in the basic multilingual plane to their value in an alphabet.
+///
+/// The table has two levels: the high byte of a code point selects a page, the low byte an entry in that page.
+/// Only pages that contain at least one character of the alphabet are stored.
+pub(crate) struct DecodeTable<const PAGES: usize> {
+    /// Page number (plus one) for each high byte, 0 if the page is empty.
+    pages: [u8; 256],
+    entries: [[u16; 256]; PAGES],
+}
+
+const INVALID: u16 = u16::MAX;
+
+impl<const PAGES: usize> DecodeTable<PAGES> {
+    /// Build the table, mapping every character of `alphabet` to its index.
+    ///
+    /// Since the table is built at compile time, a character appearing twice (which can't be decoded) fails the build.
+    pub(crate) const fn new(alphabet: &[char]) -> Self {
+        let mut table = Self {
+            pages: [0; 256],
+            entries: [[INVALID; 256]; PAGES],
+        };
+
+        let mut i = 0;
+        while i < alphabet.len() {
+            table.insert(alphabet[i], i as u16);
+            i += 1;
+        }
+
+        table
+    }
+
+    /// Additionally map `ch` to `value`.
+    pub(crate) const fn with(mut self, ch: char, value: u16) -> Self {
+        self.insert(ch, value);
+        self
+    }
+
+    /// Map `ch` to `value` in place, which avoids copying large tables at compile time.
+    const fn insert(&mut self, ch: char, value: u16) {
+        let code = ch as u32;
+        assert!(
+            code <= 0xFFFF,
+            "character outside of the basic multilingual plane"
+        );
+
+        let high = (code >> 8) as usize;
+        let low = (code & 0xFF) as usize;
+
+        if self.pages[high] == 0 {
+            let mut used = 0;
+            let mut i = 0;
+            while i < 256 {
+                if self.pages[i] as usize > used {
+                    used = self.pages[i] as usize;
+                }
+                i += 1;
+            }
+
+            assert!(used < PAGES, "too many pages for decode table");
+            self.pages[high] = (used + 1) as u8;
+        }
+
+        let page = self.pages[high] as usize - 1;
+        assert!(
+            self.entries[page][low] == INVALID,
+            "duplicate character in decode table"
+        );
+        self.entries[page][low] = value;
+    }
+
+    /// Look up the value of a character.
+    #[inline]
+    pub(crate) fn get(&self, ch: char) -> Option<u16> {
+        let code = ch as u32;
+        if code > 0xFFFF {
+            return None;
+        }
+
+        let page = self.pages[(code >> 8) as usize];
+        if page == 0 {
+            return None;
+        }
+
+        match self.entries[page as usize - 1][(code & 0xFF) as usize] {
+            INVALID => None,
+            value => Some(value),
+        }
+    }
+}
+
+/// Count the pages needed to build a [DecodeTable] for `alphabet`.
+pub(crate) const fn page_count(alphabet: &[char]) -> usize {
+    let mut seen = [false; 256];
+    let mut count = 0;
+
+    let mut i = 0;
+    while i < alphabet.len() {
+        let high = (alphabet[i] as u32 >> 8) as usize;
+        if high < 256 && !seen[high] {
+            seen[high] = true;
+            count += 1;
+        }
+        i += 1;
+    }
+
+    count
+}
@@ -0,0 +1,41 @@
+use rayon::prelude::*;
+
+use super::Cipher;
+use crate::NcrError;
+
+/// Decrypt many texts in parallel, using the same cipher.
+///
+/// The results are in the same order as the texts, one for each text.
+///
+/// # Examples
+///
+/// ```
+/// use ncr::{
+///     encoding::Base64rEncoding,
+///     encryption::{decrypt_batch, Cipher, GcmCipher},
+///     AesKey,
+/// };
+///
+/// let cipher = GcmCipher::<Base64rEncoding>::new(&AesKey::gen_random_key());
+///
+/// let mut lines: Vec<String> = (0..100)
+///     .map(|i| cipher.encrypt(&format!("#%Message {i}")).unwrap())
+///     .collect();
+/// lines.push("Not encrypted".to_owned());
+///
+/// let decrypted = decrypt_batch(&cipher, &lines);
+///
+/// assert_eq!(decrypted.len(), 101);
+/// assert_eq!(decrypted[42].as_deref(), Ok("#%Message 42"));
+/// assert!(decrypted[100].is_err());
+/// ```
+pub fn decrypt_batch<C, S>(cipher: &C, ciphertexts: &[S]) -> Vec<Result<String, NcrError>>
+where
+    C: Cipher + Sync,
+    S: AsRef<str> + Sync,
+{
+    ciphertexts
+        .par_iter()
+        .map(|ciphertext| cipher.decrypt(ciphertext.as_ref()))
+        .collect()
+}
@@ -1,13 +1,13 @@
 use aes::{
-    cipher::{AsyncStreamCipher, KeyIvInit},
+    cipher::{AsyncStreamCipher, InnerIvInit, KeyInit},
     Aes128,
 };
 use cfb8::{Decryptor, Encryptor};
 use rand::Rng;
-use std::{convert::Infallible, marker::PhantomData, num::Wrapping};
+use std::{convert::Infallible, fmt, marker::PhantomData};
 
-use super::Encryption;
-use crate::{encoding::Encoding, AesKey, NcrError};
+use super::{Cipher, Encryption};
+use crate::{encoding::Encoding, AesKey, JavaRandom, NcrError};
 
 /// The aes/cfb8 encryption.
 #[derive(Debug)]
@@ -24,8 +24,24 @@ pub struct Cfb8Encryption<E: Encoding>(PhantomData<E>);
 //     Nonce is fed into java.util.Random as seed to generate IV, which is used for encryption.
 //     Ciphertext is the plaintext after encryption (same length as plaintext).
 
-impl<E: Encoding> Cfb8Encryption<E> {
-    fn raw_encrypt(plaintext: &[u8], key: &AesKey) -> Vec<u8> {
+/// The aes/cfb8 encryption, with a pre-expanded key.
+///
+/// See [Cfb8Encryption].
+pub struct Cfb8Cipher<E: Encoding> {
+    cipher: Aes128,
+    _encoding: PhantomData<E>,
+}
+
+impl<E: Encoding> Cfb8Cipher<E> {
+    /// Create a cipher from a key.
+    pub fn new(key: &AesKey) -> Self {
+        Self {
+            cipher: Aes128::new(key.as_ref().into()),
+            _encoding: PhantomData,
+        }
+    }
+
+    pub(crate) fn raw_encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
         let mut output = Vec::with_capacity(8 + plaintext.len());
         let nonce = rand::thread_rng().gen::<[u8; 8]>();
 
@@ -34,12 +50,12 @@ impl<E: Encoding> Cfb8Encryption<E> {
 
         let iv = generate_iv(u64::from_be_bytes(nonce));
 
-        Encryptor::<Aes128>::new(key.as_ref().into(), &iv.into()).encrypt(&mut output[8..]);
+        Encryptor::inner_iv_init(self.cipher.clone(), &iv.into()).encrypt(&mut output[8..]);
 
         output
     }
 
-    fn raw_decrypt(ciphertext: Vec<u8>, key: &AesKey) -> Result<String, NcrError> {
+    pub(crate) fn raw_decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, NcrError> {
         if ciphertext.len() < 8 {
             return Err(NcrError::DecryptError);
         }
@@ -48,29 +64,32 @@ impl<E: Encoding> Cfb8Encryption<E> {
         let iv = generate_iv(u64::from_be_bytes(nonce));
 
         let mut output = Vec::from(&ciphertext[8..]);
-        Decryptor::<Aes128>::new(key.as_ref().into(), &iv.into()).decrypt(&mut output);
+        Decryptor::inner_iv_init(self.cipher.clone(), &iv.into()).decrypt(&mut output);
 
-        String::from_utf8(output).map_err(|_| NcrError::DecryptError)
+        Ok(output)
     }
 }
 
-fn generate_iv(nonce: u64) -> [u8; 16] {
-    /// Modulus
-    const M: Wrapping<i64> = Wrapping((1 << 48) - 1);
-    /// Multiplier
-    const A: Wrapping<i64> = Wrapping(0x5DEECE66D);
-    /// Increment
-    const C: Wrapping<i64> = Wrapping(11);
-
-    let mut iv = [0u8; 16];
-
-    let mut state = Wrapping((nonce as i64) ^ A.0) & M;
+impl<E: Encoding> Cipher for Cfb8Cipher<E> {
+    fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<String, NcrError> {
+        Ok(E::encode(&self.raw_encrypt(plaintext)))
+    }
 
-    for chunk in iv.chunks_exact_mut(4) {
-        state = (state * A + C) & M;
+    fn decrypt_bytes(&self, ciphertext: &str) -> Result<Vec<u8>, NcrError> {
+        self.raw_decrypt(&E::decode(ciphertext)?)
+    }
+}
 
-        chunk.copy_from_slice(&(((state.0 as u64) >> 16) as i32).to_le_bytes());
+impl<E: Encoding> fmt::Debug for Cfb8Cipher<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cfb8Cipher").finish_non_exhaustive()
     }
+}
+
+/// Generate the IV from a nonce, like the mod: `new Random(nonce).nextBytes(iv)`.
+fn generate_iv(nonce: u64) -> [u8; 16] {
+    let mut iv = [0u8; 16];
+    JavaRandom::new(nonce as i64).next_bytes(&mut iv);
 
     iv
 }
@@ -81,14 +100,12 @@ impl<E: Encoding> Encryption for Cfb8Encryption<E> {
     type DecryptError = NcrError;
 
     fn encrypt(plaintext: &str, key: &AesKey) -> Result<String, Infallible> {
-        let ciphertext = Self::raw_encrypt(plaintext.as_bytes(), key);
+        let ciphertext = Cfb8Cipher::<E>::new(key).raw_encrypt(plaintext.as_bytes());
 
         Ok(E::encode(&ciphertext))
     }
 
     fn decrypt(ciphertext: &str, key: &AesKey) -> Result<String, NcrError> {
-        let ciphertext = E::decode(ciphertext)?;
-
-        Self::raw_decrypt(ciphertext, key)
+        Cfb8Cipher::<E>::new(key).decrypt(ciphertext)
     }
 }
@@ -0,0 +1,103 @@
+use miniz_oxide::{deflate::compress_to_vec, inflate::decompress_to_vec_with_limit};
+
+use super::Cipher;
+use crate::NcrError;
+
+/// Compression before encryption, for any cipher.
+///
+/// The plaintext is compressed with raw deflate, unless that makes it longer.
+/// A flag byte tells the receiver whether the payload is compressed.
+/// Only clients using this wrapper can decrypt the messages.
+///
+/// # Examples
+///
+/// ```
+/// use ncr::{
+///     encoding::Base64rEncoding,
+///     encryption::{Cfb8Cipher, Cipher, Compressed},
+///     AesKey,
+/// };
+///
+/// let key = AesKey::gen_from_passphrase(b"secret");
+/// let cipher = Cfb8Cipher::<Base64rEncoding>::new(&key);
+/// let compressed = Compressed::new(Cfb8Cipher::<Base64rEncoding>::new(&key));
+///
+/// let plaintext = "#%the quick brown fox jumps over the lazy dog, the quick brown fox jumps over the lazy dog";
+///
+/// let ciphertext = compressed.encrypt(plaintext).unwrap();
+/// assert!(ciphertext.chars().count() < cipher.encrypt(plaintext).unwrap().chars().count());
+///
+/// assert_eq!(compressed.decrypt(&ciphertext).unwrap(), plaintext);
+/// ```
+#[derive(Debug)]
+pub struct Compressed<C: Cipher> {
+    cipher: C,
+}
+
+// Compressed payload:
+// This diagram shows the bytes fed into the inner cipher.
+//
+// |  1   -    Var    | (bytes)
+// | Flag |  Payload  |
+// |------------------|
+//
+// Where:
+//     Flag is 0 if the payload is the plaintext, or 1 if the payload is the plaintext compressed with raw deflate.
+
+const FLAG_RAW: u8 = 0;
+const FLAG_DEFLATE: u8 = 1;
+
+/// Maximum size of a decompressed message, to guard against decompression bombs.
+const MAX_DECOMPRESSED_LEN: usize = 1 << 16;
+
+impl<C: Cipher> Compressed<C> {
+    /// Wrap a cipher.
+    pub fn new(cipher: C) -> Self {
+        Self { cipher }
+    }
+
+    /// Returns the underlying cipher.
+    #[inline]
+    pub fn cipher(&self) -> &C {
+        &self.cipher
+    }
+
+    /// Consumes the wrapper, returns the underlying cipher.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.cipher
+    }
+}
+
+impl<C: Cipher> Cipher for Compressed<C> {
+    fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<String, NcrError> {
+        let compressed = compress_to_vec(plaintext, 10);
+
+        let mut payload = Vec::with_capacity(1 + plaintext.len().min(compressed.len()));
+        if compressed.len() < plaintext.len() {
+            payload.push(FLAG_DEFLATE);
+            payload.extend_from_slice(&compressed);
+        } else {
+            payload.push(FLAG_RAW);
+            payload.extend_from_slice(plaintext);
+        }
+
+        self.cipher.encrypt_bytes(&payload)
+    }
+
+    fn decrypt_bytes(&self, ciphertext: &str) -> Result<Vec<u8>, NcrError> {
+        let mut payload = self.cipher.decrypt_bytes(ciphertext)?;
+
+        match payload.first() {
+            Some(&FLAG_RAW) => {
+                payload.remove(0);
+                Ok(payload)
+            }
+            Some(&FLAG_DEFLATE) => {
+                decompress_to_vec_with_limit(&payload[1..], MAX_DECOMPRESSED_LEN)
+                    .map_err(|_| NcrError::DecryptError)
+            }
+            _ => Err(NcrError::DecryptError),
+        }
+    }
+}
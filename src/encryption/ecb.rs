@@ -1,10 +1,10 @@
 use aes::{
-    cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyInit},
-    Aes128Dec, Aes128Enc,
+    cipher::{block_padding::Pkcs7, BlockDecrypt, BlockEncrypt, KeyInit},
+    Aes128,
 };
-use std::{convert::Infallible, marker::PhantomData};
+use std::{convert::Infallible, fmt, marker::PhantomData};
 
-use super::Encryption;
+use super::{Cipher, Encryption};
 use crate::{encoding::Encoding, AesKey, NcrError};
 
 /// The aes/ecb encryption.
@@ -21,23 +21,49 @@ pub struct EcbEncryption<E: Encoding>(PhantomData<E>);
 // Where:
 //     Ciphertext is the plaintext after encryption (same length as plaintext).
 
-impl<E: Encoding> EcbEncryption<E> {
-    fn raw_encrypt(plaintext: &[u8], key: &AesKey) -> Vec<u8> {
-        let cipher = Aes128Enc::new(key.as_ref().into());
+/// The aes/ecb encryption, with a pre-expanded key.
+///
+/// See [EcbEncryption].
+pub struct EcbCipher<E: Encoding> {
+    cipher: Aes128,
+    _encoding: PhantomData<E>,
+}
 
-        // Pkcs5 is a subset of Pkcs7.
-        cipher.encrypt_padded_vec_mut::<Pkcs7>(plaintext)
+impl<E: Encoding> EcbCipher<E> {
+    /// Create a cipher from a key.
+    pub fn new(key: &AesKey) -> Self {
+        Self {
+            cipher: Aes128::new(key.as_ref().into()),
+            _encoding: PhantomData,
+        }
     }
 
-    fn raw_decrypt(ciphertext: Vec<u8>, key: &AesKey) -> Result<String, NcrError> {
-        let cipher = Aes128Dec::new(key.as_ref().into());
+    pub(crate) fn raw_encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        // Pkcs5 is a subset of Pkcs7.
+        self.cipher.encrypt_padded_vec::<Pkcs7>(plaintext)
+    }
 
+    pub(crate) fn raw_decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, NcrError> {
         // Pkcs5 is a subset of Pkcs7.
-        let output = cipher
-            .decrypt_padded_vec_mut::<Pkcs7>(&ciphertext)
-            .map_err(|_| NcrError::DecryptError)?;
+        self.cipher
+            .decrypt_padded_vec::<Pkcs7>(ciphertext)
+            .map_err(|_| NcrError::DecryptError)
+    }
+}
 
-        String::from_utf8(output).map_err(|_| NcrError::DecryptError)
+impl<E: Encoding> Cipher for EcbCipher<E> {
+    fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<String, NcrError> {
+        Ok(E::encode(&self.raw_encrypt(plaintext)))
+    }
+
+    fn decrypt_bytes(&self, ciphertext: &str) -> Result<Vec<u8>, NcrError> {
+        self.raw_decrypt(&E::decode(ciphertext)?)
+    }
+}
+
+impl<E: Encoding> fmt::Debug for EcbCipher<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EcbCipher").finish_non_exhaustive()
     }
 }
 
@@ -47,14 +73,12 @@ impl<E: Encoding> Encryption for EcbEncryption<E> {
     type DecryptError = NcrError;
 
     fn encrypt(plaintext: &str, key: &AesKey) -> Result<String, Infallible> {
-        let ciphertext = Self::raw_encrypt(plaintext.as_bytes(), key);
+        let ciphertext = EcbCipher::<E>::new(key).raw_encrypt(plaintext.as_bytes());
 
         Ok(E::encode(&ciphertext))
     }
 
     fn decrypt(ciphertext: &str, key: &AesKey) -> Result<String, NcrError> {
-        let ciphertext = E::decode(ciphertext)?;
-
-        Self::raw_decrypt(ciphertext, key)
+        EcbCipher::<E>::new(key).decrypt(ciphertext)
     }
 }
@@ -0,0 +1,215 @@
+use std::fmt;
+
+use super::{Cfb8Cipher, Cipher, GcmCipher};
+use crate::{encoding::Encoding, AesKey, NcrError};
+
+/// The algorithm of an enveloped message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Algorithm {
+    Cfb8 = 1,
+    Gcm = 2,
+}
+
+impl Algorithm {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Algorithm::Cfb8),
+            2 => Some(Algorithm::Gcm),
+            _ => None,
+        }
+    }
+}
+
+/// A self-describing envelope around aes/cfb8 and aes/gcm messages.
+///
+/// Every message starts with a short header identifying the algorithm and the key,
+/// so receivers pick the right one directly instead of guessing.
+/// With [Algorithm::Gcm], the header is authenticated along with the message.
+/// Messages without the header (plain No Chat Reports messages) are decrypted with the fallback algorithm and the default key.
+///
+/// # Examples
+///
+/// ```
+/// use ncr::{
+///     encoding::Base64rEncoding,
+///     encryption::{Algorithm, Cfb8Cipher, Cipher, EnvelopeCipher},
+///     AesKey,
+/// };
+///
+/// let old_key = AesKey::gen_random_key();
+/// let new_key = AesKey::gen_random_key();
+///
+/// let sender = EnvelopeCipher::<Base64rEncoding>::new(Algorithm::Gcm, 2, &new_key);
+/// let receiver = EnvelopeCipher::<Base64rEncoding>::new(Algorithm::Cfb8, 1, &old_key).with_key(2, &new_key);
+///
+/// let ciphertext = sender.encrypt("#%Hello, world!").unwrap();
+/// assert_eq!(receiver.decrypt(&ciphertext).unwrap(), "#%Hello, world!");
+///
+/// // Plain messages are still understood.
+/// let ciphertext = Cfb8Cipher::<Base64rEncoding>::new(&old_key).encrypt("#%Hello, world!").unwrap();
+/// assert_eq!(receiver.decrypt(&ciphertext).unwrap(), "#%Hello, world!");
+/// ```
+///
+/// ## Authenticated header
+///
+/// ```
+/// use ncr::{
+///     encoding::{Base64rEncoding, Encoding},
+///     encryption::{Algorithm, Cipher, EnvelopeCipher},
+///     AesKey,
+/// };
+///
+/// let key = AesKey::gen_random_key();
+/// let cipher = EnvelopeCipher::<Base64rEncoding>::new(Algorithm::Gcm, 1, &key).with_key(2, &key);
+///
+/// let mut bytes = Base64rEncoding::decode(&cipher.encrypt("#%Hello, world!").unwrap()).unwrap();
+///
+/// // Claim the message was sent with another key id.
+/// bytes[4] = 2;
+/// assert!(cipher.decrypt(&Base64rEncoding::encode(&bytes)).is_err());
+/// ```
+pub struct EnvelopeCipher<E: Encoding> {
+    algorithm: Algorithm,
+    fallback: Algorithm,
+    /// The first key is the default key.
+    keys: Vec<EnvelopeKey<E>>,
+}
+
+struct EnvelopeKey<E: Encoding> {
+    id: u8,
+    cfb8: Cfb8Cipher<E>,
+    gcm: GcmCipher<E>,
+}
+
+// Envelope:
+// This diagram shows the raw bytes used before encoding (and after decoding).
+//
+// |   2   -    1    -     1     -   1    -    Var    | (bytes)
+// | Magic | Version | Algorithm | Key ID |  Payload  |
+// |--------------------------------------------------|
+//
+// Where:
+//     Magic is "NC".
+//     Version is 1.
+//     Algorithm is the algorithm of the payload (1 for aes/cfb8, 2 for aes/gcm).
+//     Key ID identifies the key used for the payload.
+//     Payload is the raw bytes of the algorithm (see Cfb8Encryption and GcmEncryption).
+//
+// With aes/gcm, the header is passed as associated data, so changing any of it makes decryption fail.
+// Aes/cfb8 can't authenticate anything, including the header.
+//
+// A plain message starts with a random nonce (or IV), so it looks like an envelope with probability 2^-24.
+// If decrypting it as an envelope fails (unknown algorithm or key id, or a gcm tag mismatch),
+// it is decrypted as a plain message. But if the header names aes/cfb8 and a known key id,
+// decrypting it as an envelope succeeds with garbage, and the plain message is lost.
+// This happens with probability 2^-40 per known key id.
+
+const MAGIC: [u8; 2] = *b"NC";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 5;
+
+impl<E: Encoding> EnvelopeCipher<E> {
+    /// Create a cipher that encrypts with `algorithm` and `key`, identified by `key_id`.
+    ///
+    /// This key is also the default key, used for messages without an envelope,
+    /// which are decrypted with the same algorithm unless changed with [with_fallback](Self::with_fallback).
+    pub fn new(algorithm: Algorithm, key_id: u8, key: &AesKey) -> Self {
+        Self {
+            algorithm,
+            fallback: algorithm,
+            keys: vec![EnvelopeKey::new(key_id, key)],
+        }
+    }
+
+    /// Add a key for decryption.
+    ///
+    /// If a key with the same id already exists, it is replaced.
+    pub fn with_key(mut self, key_id: u8, key: &AesKey) -> Self {
+        let key = EnvelopeKey::new(key_id, key);
+
+        match self.keys.iter_mut().find(|existing| existing.id == key_id) {
+            Some(existing) => *existing = key,
+            None => self.keys.push(key),
+        }
+
+        self
+    }
+
+    /// Set the algorithm for messages without an envelope.
+    pub fn with_fallback(mut self, algorithm: Algorithm) -> Self {
+        self.fallback = algorithm;
+        self
+    }
+
+    fn raw_decrypt_envelope(&self, ciphertext: &[u8]) -> Result<Vec<u8>, NcrError> {
+        if ciphertext.len() < HEADER_LEN || ciphertext[..2] != MAGIC || ciphertext[2] != VERSION {
+            return Err(NcrError::DecryptError);
+        }
+
+        let algorithm = Algorithm::from_byte(ciphertext[3]).ok_or(NcrError::DecryptError)?;
+        let key = self
+            .keys
+            .iter()
+            .find(|key| key.id == ciphertext[4])
+            .ok_or(NcrError::DecryptError)?;
+
+        let (header, payload) = ciphertext.split_at(HEADER_LEN);
+
+        match algorithm {
+            Algorithm::Cfb8 => key.cfb8.raw_decrypt(payload),
+            Algorithm::Gcm => key.gcm.raw_decrypt_with_aad(payload, header),
+        }
+    }
+}
+
+impl<E: Encoding> EnvelopeKey<E> {
+    fn new(id: u8, key: &AesKey) -> Self {
+        Self {
+            id,
+            cfb8: Cfb8Cipher::new(key),
+            gcm: GcmCipher::new(key),
+        }
+    }
+
+    fn raw_decrypt(&self, algorithm: Algorithm, ciphertext: &[u8]) -> Result<Vec<u8>, NcrError> {
+        match algorithm {
+            Algorithm::Cfb8 => self.cfb8.raw_decrypt(ciphertext),
+            Algorithm::Gcm => self.gcm.raw_decrypt(ciphertext),
+        }
+    }
+}
+
+impl<E: Encoding> Cipher for EnvelopeCipher<E> {
+    fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<String, NcrError> {
+        let key = &self.keys[0];
+
+        let mut output = Vec::with_capacity(HEADER_LEN + plaintext.len() + 24);
+        output.extend_from_slice(&MAGIC);
+        output.extend_from_slice(&[VERSION, self.algorithm as u8, key.id]);
+
+        let payload = match self.algorithm {
+            Algorithm::Cfb8 => key.cfb8.raw_encrypt(plaintext),
+            Algorithm::Gcm => key.gcm.raw_encrypt_with_aad(plaintext, &output),
+        };
+        output.extend_from_slice(&payload);
+
+        Ok(E::encode(&output))
+    }
+
+    fn decrypt_bytes(&self, ciphertext: &str) -> Result<Vec<u8>, NcrError> {
+        let ciphertext = E::decode(ciphertext)?;
+
+        self.raw_decrypt_envelope(&ciphertext)
+            .or_else(|_| self.keys[0].raw_decrypt(self.fallback, &ciphertext))
+    }
+}
+
+impl<E: Encoding> fmt::Debug for EnvelopeCipher<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnvelopeCipher")
+            .field("algorithm", &self.algorithm)
+            .field("fallback", &self.fallback)
+            .finish_non_exhaustive()
+    }
+}
@@ -3,10 +3,22 @@ use aes::{
     Aes128,
 };
 use aes_gcm::{AeadInPlace, AesGcm};
+use hmac::{Hmac, Mac};
 use rand::Rng;
-use std::{convert::Infallible, marker::PhantomData};
+use sha2::Sha256;
+use std::{
+    collections::{HashSet, VecDeque},
+    convert::Infallible,
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use super::Encryption;
+use super::{Cipher, Encryption};
 use crate::{encoding::Encoding, AesKey, NcrError};
 
 /// The aes/gcm encryption.
@@ -25,18 +37,246 @@ pub struct GcmEncryption<E: Encoding>(PhantomData<E>);
 //     Ciphertext is the plaintext after encryption (same length as plaintext).
 //     Tag is the GCM Authorization Tag (decryption would fail if tag doesn't match).
 
-impl<E: Encoding> GcmEncryption<E> {
-    fn raw_encrypt(plaintext: &[u8], key: &AesKey) -> Vec<u8> {
+/// How the IV of each message is chosen.
+///
+/// The IV must never repeat with the same key, or the authentication key leaks and messages can be forged.
+/// Every strategy produces a 12 bytes IV in the same place,
+/// so messages can be decrypted by anyone (including the mod) whatever the strategy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonceStrategy {
+    /// A random IV for every message, like the mod.
+    #[default]
+    Random,
+    /// A 4 bytes sender id followed by a 8 bytes counter.
+    ///
+    /// The IV never repeats as long as every sender using the key has a different id.
+    /// The counter starts at the current time in microseconds,
+    /// so it keeps increasing across restarts (unless sending more than a million messages per second).
+    Counter { sender: u32 },
+    /// An IV derived from 16 random bytes and the message with HMAC-SHA256.
+    ///
+    /// A broken random number generator only repeats the IV if the message is the same too.
+    Synthetic,
+}
+
+enum Nonce {
+    Random,
+    Counter { sender: u32, counter: AtomicU64 },
+    Synthetic(Hmac<Sha256>),
+}
+
+impl Nonce {
+    fn new(key: &AesKey, strategy: NonceStrategy) -> Self {
+        match strategy {
+            NonceStrategy::Random => Nonce::Random,
+            NonceStrategy::Counter { sender } => {
+                let start = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |duration| duration.as_micros() as u64);
+
+                Nonce::Counter {
+                    sender,
+                    counter: AtomicU64::new(start),
+                }
+            }
+            NonceStrategy::Synthetic => {
+                // Use a separate key for the derivation, never the aes key itself.
+                let mut mac = <Hmac<Sha256> as KeyInit>::new_from_slice(key.as_ref()).unwrap();
+                mac.update(b"ncr gcm synthetic nonce");
+                let nonce_key = mac.finalize().into_bytes();
+
+                Nonce::Synthetic(<Hmac<Sha256> as KeyInit>::new_from_slice(&nonce_key).unwrap())
+            }
+        }
+    }
+
+    fn generate(&self, plaintext: &[u8], aad: &[u8]) -> [u8; 12] {
+        match self {
+            Nonce::Random => rand::thread_rng().gen(),
+            Nonce::Counter { sender, counter } => {
+                let mut iv = [0u8; 12];
+                iv[..4].copy_from_slice(&sender.to_be_bytes());
+                iv[4..].copy_from_slice(&counter.fetch_add(1, Ordering::Relaxed).to_be_bytes());
+                iv
+            }
+            Nonce::Synthetic(mac) => {
+                let mut mac = mac.clone();
+                mac.update(&rand::thread_rng().gen::<[u8; 16]>());
+                mac.update(&(aad.len() as u64).to_be_bytes());
+                mac.update(aad);
+                mac.update(plaintext);
+
+                mac.finalize().into_bytes()[..12].try_into().unwrap()
+            }
+        }
+    }
+}
+
+/// The IVs of the last received messages.
+struct SeenNonces {
+    seen: HashSet<[u8; 12]>,
+    order: VecDeque<[u8; 12]>,
+    capacity: usize,
+}
+
+impl SeenNonces {
+    /// Returns false if the IV was already seen.
+    fn insert(&mut self, iv: [u8; 12]) -> bool {
+        if !self.seen.insert(iv) {
+            return false;
+        }
+
+        self.order.push_back(iv);
+        if self.order.len() > self.capacity {
+            let oldest = self.order.pop_front().unwrap();
+            self.seen.remove(&oldest);
+        }
+
+        true
+    }
+}
+
+/// The aes/gcm encryption, with a pre-expanded key.
+///
+/// See [GcmEncryption].
+pub struct GcmCipher<E: Encoding> {
+    cipher: AesGcm<Aes128, U12, U12>,
+    nonce: Nonce,
+    seen: Option<Mutex<SeenNonces>>,
+    _encoding: PhantomData<E>,
+}
+
+impl<E: Encoding> GcmCipher<E> {
+    /// Create a cipher from a key, using random IVs.
+    #[inline]
+    pub fn new(key: &AesKey) -> Self {
+        Self::with_nonce(key, NonceStrategy::Random)
+    }
+
+    /// Create a cipher from a key, choosing IVs with the given strategy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ncr::{
+    ///     encoding::Base64rEncoding,
+    ///     encryption::{Cipher, GcmCipher, NonceStrategy},
+    ///     AesKey,
+    /// };
+    ///
+    /// let key = AesKey::gen_from_passphrase(b"secret");
+    /// let bot = GcmCipher::<Base64rEncoding>::with_nonce(&key, NonceStrategy::Counter { sender: 7 });
+    ///
+    /// let encrypted = bot.encrypt("#%Hello, world!").unwrap();
+    ///
+    /// // Readable by anyone with the key.
+    /// let player = GcmCipher::<Base64rEncoding>::new(&key);
+    /// assert_eq!(player.decrypt(&encrypted).unwrap(), "#%Hello, world!");
+    /// ```
+    pub fn with_nonce(key: &AesKey, strategy: NonceStrategy) -> Self {
+        Self {
+            cipher: AesGcm::new(key.as_ref().into()),
+            nonce: Nonce::new(key, strategy),
+            seen: None,
+            _encoding: PhantomData,
+        }
+    }
+
+    /// Reject messages whose IV was already seen, remembering the IVs of the last `capacity` messages.
+    ///
+    /// A repeated IV means the message is replayed, or the sender reused an IV.
+    /// Decrypting such a message returns [NcrError::ReplayError].
+    /// Only messages which decrypt successfully are remembered, so forged messages can't fill the history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ncr::{
+    ///     encoding::Base64rEncoding,
+    ///     encryption::{Cipher, GcmCipher},
+    ///     AesKey, NcrError,
+    /// };
+    ///
+    /// let key = AesKey::gen_from_passphrase(b"secret");
+    /// let cipher = GcmCipher::<Base64rEncoding>::new(&key).with_nonce_check(1024);
+    ///
+    /// let encrypted = cipher.encrypt("#%Hello, world!").unwrap();
+    ///
+    /// assert_eq!(cipher.decrypt(&encrypted).unwrap(), "#%Hello, world!");
+    /// assert_eq!(cipher.decrypt(&encrypted), Err(NcrError::ReplayError));
+    /// ```
+    pub fn with_nonce_check(mut self, capacity: usize) -> Self {
+        self.seen = Some(Mutex::new(SeenNonces {
+            seen: HashSet::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+            capacity,
+        }));
+        self
+    }
+
+    /// Encrypt a given text, binding it to associated data.
+    ///
+    /// The associated data (such as the sender name or the server address) isn't included in the ciphertext,
+    /// but the same associated data must be given to [GcmCipher::decrypt_with_aad].
+    /// When it holds several fields, separate them unambiguously (e.g. with a `\0`).
+    ///
+    /// Encrypting with empty associated data is the same as [Cipher::encrypt].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ncr::{encoding::Base64rEncoding, encryption::GcmCipher, AesKey};
+    ///
+    /// let cipher = GcmCipher::<Base64rEncoding>::new(&AesKey::gen_from_passphrase(b"secret"));
+    ///
+    /// let encrypted = cipher.encrypt_with_aad("#%Hello, world!", b"Alice\0play.example.com").unwrap();
+    ///
+    /// assert_eq!(
+    ///     cipher.decrypt_with_aad(&encrypted, b"Alice\0play.example.com").unwrap(),
+    ///     "#%Hello, world!"
+    /// );
+    ///
+    /// // Replayed under another player's name.
+    /// assert!(cipher.decrypt_with_aad(&encrypted, b"Bob\0play.example.com").is_err());
+    /// ```
+    pub fn encrypt_with_aad(&self, plaintext: &str, aad: &[u8]) -> Result<String, NcrError> {
+        Ok(E::encode(
+            &self.raw_encrypt_with_aad(plaintext.as_bytes(), aad),
+        ))
+    }
+
+    /// Decrypt a given text, checking it was encrypted with the same associated data.
+    ///
+    /// # Error
+    ///
+    /// This return a error if decryption fails (including when the associated data doesn't match)
+    /// or the decrypted bytes aren't valid utf-8.
+    pub fn decrypt_with_aad(&self, ciphertext: &str, aad: &[u8]) -> Result<String, NcrError> {
+        let plaintext = self.raw_decrypt_with_aad(&E::decode(ciphertext)?, aad)?;
+
+        String::from_utf8(plaintext).map_err(|_| NcrError::DecryptError)
+    }
+
+    #[inline]
+    pub(crate) fn raw_encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        self.raw_encrypt_with_aad(plaintext, &[])
+    }
+
+    #[inline]
+    pub(crate) fn raw_decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, NcrError> {
+        self.raw_decrypt_with_aad(ciphertext, &[])
+    }
+
+    pub(crate) fn raw_encrypt_with_aad(&self, plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
         let mut output = Vec::with_capacity(plaintext.len() + 24);
-        let iv = rand::thread_rng().gen::<[u8; 12]>();
+        let iv = self.nonce.generate(plaintext, aad);
 
         output.extend_from_slice(&iv);
         output.extend_from_slice(plaintext);
 
-        let cipher = AesGcm::<Aes128, U12, U12>::new(key.as_ref().into());
-
-        let tag = cipher
-            .encrypt_in_place_detached(&iv.into(), &[], &mut output[12..])
+        let tag = self
+            .cipher
+            .encrypt_in_place_detached(&iv.into(), aad, &mut output[12..])
             .unwrap();
 
         output.extend_from_slice(&tag);
@@ -44,7 +284,11 @@ impl<E: Encoding> GcmEncryption<E> {
         output
     }
 
-    fn raw_decrypt(ciphertext: Vec<u8>, key: &AesKey) -> Result<String, NcrError> {
+    pub(crate) fn raw_decrypt_with_aad(
+        &self,
+        ciphertext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, NcrError> {
         if ciphertext.len() < 24 {
             return Err(NcrError::DecryptError);
         }
@@ -54,13 +298,55 @@ impl<E: Encoding> GcmEncryption<E> {
 
         let mut output = Vec::from(&ciphertext[12..(ciphertext.len() - 12)]);
 
-        let cipher = AesGcm::<Aes128, U12, U12>::new(key.as_ref().into());
-
-        cipher
-            .decrypt_in_place_detached(&iv.into(), &[], &mut output, &tag.into())
+        self.cipher
+            .decrypt_in_place_detached(&iv.into(), aad, &mut output, &tag.into())
             .map_err(|_| NcrError::DecryptError)?;
 
-        String::from_utf8(output).map_err(|_| NcrError::DecryptError)
+        if let Some(seen) = &self.seen {
+            if !seen.lock().unwrap().insert(iv) {
+                return Err(NcrError::ReplayError);
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+impl<E: Encoding> Cipher for GcmCipher<E> {
+    fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<String, NcrError> {
+        Ok(E::encode(&self.raw_encrypt(plaintext)))
+    }
+
+    fn decrypt_bytes(&self, ciphertext: &str) -> Result<Vec<u8>, NcrError> {
+        self.raw_decrypt(&E::decode(ciphertext)?)
+    }
+}
+
+impl<E: Encoding> fmt::Debug for GcmCipher<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GcmCipher").finish_non_exhaustive()
+    }
+}
+
+impl<E: Encoding> GcmEncryption<E> {
+    /// Encrypt a given text, binding it to associated data.
+    ///
+    /// See [GcmCipher::encrypt_with_aad].
+    #[inline]
+    pub fn encrypt_with_aad(plaintext: &str, key: &AesKey, aad: &[u8]) -> Result<String, NcrError> {
+        GcmCipher::<E>::new(key).encrypt_with_aad(plaintext, aad)
+    }
+
+    /// Decrypt a given text, checking it was encrypted with the same associated data.
+    ///
+    /// See [GcmCipher::decrypt_with_aad].
+    #[inline]
+    pub fn decrypt_with_aad(
+        ciphertext: &str,
+        key: &AesKey,
+        aad: &[u8],
+    ) -> Result<String, NcrError> {
+        GcmCipher::<E>::new(key).decrypt_with_aad(ciphertext, aad)
     }
 }
 
@@ -70,14 +356,12 @@ impl<E: Encoding> Encryption for GcmEncryption<E> {
     type DecryptError = NcrError;
 
     fn encrypt(plaintext: &str, key: &AesKey) -> Result<String, Infallible> {
-        let ciphertext = Self::raw_encrypt(plaintext.as_bytes(), key);
+        let ciphertext = GcmCipher::<E>::new(key).raw_encrypt(plaintext.as_bytes());
 
         Ok(E::encode(&ciphertext))
     }
 
     fn decrypt(ciphertext: &str, key: &AesKey) -> Result<String, NcrError> {
-        let ciphertext = E::decode(ciphertext)?;
-
-        Self::raw_decrypt(ciphertext, key)
+        GcmCipher::<E>::new(key).decrypt(ciphertext)
     }
 }
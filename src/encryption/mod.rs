@@ -24,22 +24,63 @@
 //! assert_eq!(decrypted, "#%Hello, world!");
 //! ```
 //!
+//! ## Reusing a key
+//!
+//! [Encryption] sets up the key for every message. When handling many messages with the same key,
+//! create a [Cipher] once instead, which keeps the expanded key around.
+//!
+//! ```
+//! use ncr::{
+//!     encoding::Base64rEncoding,
+//!     encryption::{Cfb8Cipher, Cipher},
+//!     AesKey,
+//! };
+//!
+//! let key = AesKey::gen_from_passphrase(b"secret");
+//! let cipher = Cfb8Cipher::<Base64rEncoding>::new(&key);
+//!
+//! for message in ["#%Hello", "#%world!"] {
+//!     let encrypted = cipher.encrypt(message).unwrap();
+//!     let decrypted = cipher.decrypt(&encrypted).unwrap();
+//!
+//!     assert_eq!(decrypted, message);
+//! }
+//! ```
+//!
 
+#[cfg(feature = "rayon")]
+mod batch;
 mod caesar;
 #[cfg(feature = "cfb8")]
 mod cfb8;
+#[cfg(feature = "compression")]
+mod compressed;
 #[cfg(feature = "ecb")]
 mod ecb;
+#[cfg(feature = "envelope")]
+mod envelope;
 #[cfg(feature = "gcm")]
 mod gcm;
+mod padded;
+mod timestamped;
 
+use crate::NcrError;
+
+#[cfg(feature = "rayon")]
+pub use self::batch::decrypt_batch;
 pub use self::caesar::CaesarEncryption;
 #[cfg(feature = "cfb8")]
-pub use self::cfb8::Cfb8Encryption;
+pub use self::cfb8::{Cfb8Cipher, Cfb8Encryption};
+#[cfg(feature = "compression")]
+pub use self::compressed::Compressed;
 #[cfg(feature = "ecb")]
-pub use self::ecb::EcbEncryption;
+pub use self::ecb::{EcbCipher, EcbEncryption};
+#[cfg(feature = "envelope")]
+pub use self::envelope::{Algorithm, EnvelopeCipher};
 #[cfg(feature = "gcm")]
-pub use self::gcm::GcmEncryption;
+pub use self::gcm::{GcmCipher, GcmEncryption, NonceStrategy};
+pub use self::padded::{Padded, Padding};
+pub use self::timestamped::{ReplayWindow, Timestamped};
 
 /// The encryption trait.
 pub trait Encryption {
@@ -53,3 +94,28 @@ pub trait Encryption {
     /// Decrypt a given text.
     fn decrypt(ciphertext: &str, key: &Self::KeyType) -> Result<String, Self::DecryptError>;
 }
+
+/// The cipher trait.
+///
+/// Unlike [Encryption], a cipher is created once from a key, and can then be used for many messages.
+pub trait Cipher {
+    /// Encrypt given bytes.
+    fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<String, NcrError>;
+
+    /// Decrypt a given text into bytes.
+    fn decrypt_bytes(&self, ciphertext: &str) -> Result<Vec<u8>, NcrError>;
+
+    /// Encrypt a given text.
+    fn encrypt(&self, plaintext: &str) -> Result<String, NcrError> {
+        self.encrypt_bytes(plaintext.as_bytes())
+    }
+
+    /// Decrypt a given text.
+    ///
+    /// # Error
+    ///
+    /// This return a error if decryption fails or the decrypted bytes aren't valid utf-8.
+    fn decrypt(&self, ciphertext: &str) -> Result<String, NcrError> {
+        String::from_utf8(self.decrypt_bytes(ciphertext)?).map_err(|_| NcrError::DecryptError)
+    }
+}
@@ -0,0 +1,132 @@
+use rand::Rng;
+
+use super::Cipher;
+use crate::{validation::MAX_CHAT_LENGTH, NcrError};
+
+/// How much padding to add, see [Padded].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Padding {
+    /// Pad to the smallest bucket that fits the plaintext and the padding marker.
+    ///
+    /// Plaintexts longer than every bucket only get the padding marker.
+    Buckets(Vec<usize>),
+    /// Pad with a random number of bytes, between 0 and `max` (plus the padding marker).
+    Random { max: usize },
+}
+
+impl Padding {
+    /// Buckets of 16, 32, 64 and 128 bytes.
+    pub fn default_buckets() -> Self {
+        Padding::Buckets(vec![16, 32, 64, 128])
+    }
+
+    /// The padded length of a plaintext.
+    fn target(&self, len: usize) -> usize {
+        let min = len + 1;
+
+        match self {
+            Padding::Buckets(buckets) => buckets
+                .iter()
+                .copied()
+                .filter(|bucket| *bucket >= min)
+                .min()
+                .unwrap_or(min),
+            Padding::Random { max } => min + rand::thread_rng().gen_range(0..=*max),
+        }
+    }
+}
+
+/// Length-hiding padding, for any cipher.
+///
+/// The plaintext is padded before encryption, so the length of the ciphertext doesn't reveal its exact length.
+/// The padding is removed after decryption.
+///
+/// If the padded message would be longer than the chat length limit ([MAX_CHAT_LENGTH]), it is only padded with the marker.
+///
+/// # Examples
+///
+/// ```
+/// use ncr::{
+///     encoding::Base64rEncoding,
+///     encryption::{Cipher, GcmCipher, Padded, Padding},
+///     AesKey,
+/// };
+///
+/// let cipher = Padded::new(
+///     GcmCipher::<Base64rEncoding>::new(&AesKey::gen_random_key()),
+///     Padding::default_buckets(),
+/// );
+///
+/// let yes = cipher.encrypt("#%yes").unwrap();
+/// let no = cipher.encrypt("#%no").unwrap();
+///
+/// assert_eq!(yes.chars().count(), no.chars().count());
+/// assert_eq!(cipher.decrypt(&yes).unwrap(), "#%yes");
+/// ```
+#[derive(Debug)]
+pub struct Padded<C: Cipher> {
+    cipher: C,
+    padding: Padding,
+}
+
+// Padded payload:
+// This diagram shows the bytes fed into the inner cipher.
+//
+// |    Var     -    1     -   Var   | (bytes)
+// | Plaintext  |  Marker  |  Zeros  |
+// |---------------------------------|
+//
+// Where:
+//     Marker is 0x80 (ISO/IEC 7816-4 padding).
+//     Zeros fill the payload to the padded length.
+
+const MARKER: u8 = 0x80;
+
+impl<C: Cipher> Padded<C> {
+    /// Wrap a cipher.
+    pub fn new(cipher: C, padding: Padding) -> Self {
+        Self { cipher, padding }
+    }
+
+    /// Returns the underlying cipher.
+    #[inline]
+    pub fn cipher(&self) -> &C {
+        &self.cipher
+    }
+
+    fn encrypt_padded(&self, plaintext: &[u8], target: usize) -> Result<String, NcrError> {
+        let mut payload = Vec::with_capacity(target);
+        payload.extend_from_slice(plaintext);
+        payload.push(MARKER);
+        payload.resize(target, 0);
+
+        self.cipher.encrypt_bytes(&payload)
+    }
+}
+
+impl<C: Cipher> Cipher for Padded<C> {
+    fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<String, NcrError> {
+        let target = self.padding.target(plaintext.len());
+        let output = self.encrypt_padded(plaintext, target)?;
+
+        let min = plaintext.len() + 1;
+        if target > min && output.encode_utf16().count() > MAX_CHAT_LENGTH {
+            return self.encrypt_padded(plaintext, min);
+        }
+
+        Ok(output)
+    }
+
+    fn decrypt_bytes(&self, ciphertext: &str) -> Result<Vec<u8>, NcrError> {
+        let mut payload = self.cipher.decrypt_bytes(ciphertext)?;
+
+        let marker = payload
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .filter(|i| payload[*i] == MARKER)
+            .ok_or(NcrError::DecryptError)?;
+        payload.truncate(marker);
+
+        Ok(payload)
+    }
+}
@@ -0,0 +1,183 @@
+use rand::Rng;
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::Cipher;
+use crate::NcrError;
+
+/// Timestamps and replay protection, for any cipher.
+///
+/// Every message carries a timestamp and a sequence number inside the plaintext.
+/// On decryption, the [ReplayWindow] rejects messages that were already seen, or that are too old.
+///
+/// The timestamp is only protected if the cipher is authenticated, so this should be used with
+#[cfg_attr(feature = "gcm", doc = "[GcmCipher](super::GcmCipher).")]
+#[cfg_attr(not(feature = "gcm"), doc = "`GcmCipher`.")]
+///
+/// # Examples
+///
+/// ```
+/// use ncr::{
+///     encoding::Base64rEncoding,
+///     encryption::{Cipher, GcmCipher, ReplayWindow, Timestamped},
+///     AesKey, NcrError,
+/// };
+/// use std::time::Duration;
+///
+/// let key = AesKey::gen_random_key();
+///
+/// let sender = Timestamped::new(GcmCipher::<Base64rEncoding>::new(&key));
+/// let receiver = Timestamped::new(GcmCipher::<Base64rEncoding>::new(&key))
+///     .with_window(ReplayWindow::new(Duration::from_secs(60)));
+///
+/// let ciphertext = sender.encrypt("#%Hello, world!").unwrap();
+///
+/// assert_eq!(receiver.decrypt(&ciphertext).unwrap(), "#%Hello, world!");
+/// assert_eq!(receiver.decrypt(&ciphertext), Err(NcrError::ReplayError));
+/// ```
+#[derive(Debug)]
+pub struct Timestamped<C: Cipher> {
+    cipher: C,
+    sequence: AtomicU32,
+    window: Mutex<ReplayWindow>,
+}
+
+// Timestamped payload:
+// This diagram shows the bytes fed into the inner cipher.
+//
+// |     8     -    4     -    Var     | (bytes)
+// | Timestamp | Sequence |  Plaintext |
+// |-----------------------------------|
+//
+// Where:
+//     Timestamp is the time of sending, in milliseconds since the unix epoch (big-endian).
+//     Sequence is incremented for every message, starting from a random value (big-endian).
+
+const HEADER_LEN: usize = 12;
+
+impl<C: Cipher> Timestamped<C> {
+    /// Wrap a cipher, with a replay window of 5 minutes.
+    pub fn new(cipher: C) -> Self {
+        Self {
+            cipher,
+            sequence: AtomicU32::new(rand::thread_rng().gen()),
+            window: Mutex::new(ReplayWindow::new(Duration::from_secs(300))),
+        }
+    }
+
+    /// Replace the replay window.
+    pub fn with_window(mut self, window: ReplayWindow) -> Self {
+        self.window = Mutex::new(window);
+        self
+    }
+
+    /// Returns the underlying cipher.
+    #[inline]
+    pub fn cipher(&self) -> &C {
+        &self.cipher
+    }
+}
+
+impl<C: Cipher> Cipher for Timestamped<C> {
+    fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<String, NcrError> {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+
+        let mut payload = Vec::with_capacity(HEADER_LEN + plaintext.len());
+        payload.extend_from_slice(&unix_millis(SystemTime::now()).to_be_bytes());
+        payload.extend_from_slice(&sequence.to_be_bytes());
+        payload.extend_from_slice(plaintext);
+
+        self.cipher.encrypt_bytes(&payload)
+    }
+
+    fn decrypt_bytes(&self, ciphertext: &str) -> Result<Vec<u8>, NcrError> {
+        let mut payload = self.cipher.decrypt_bytes(ciphertext)?;
+
+        if payload.len() < HEADER_LEN {
+            return Err(NcrError::DecryptError);
+        }
+
+        let timestamp = u64::from_be_bytes(payload[..8].try_into().unwrap());
+        let sequence = u32::from_be_bytes(payload[8..12].try_into().unwrap());
+
+        self.window
+            .lock()
+            .unwrap()
+            .accept(timestamp, sequence, unix_millis(SystemTime::now()))?;
+
+        payload.drain(..HEADER_LEN);
+
+        Ok(payload)
+    }
+}
+
+/// Rejects duplicate and stale messages.
+///
+/// A message is accepted if its timestamp is within `max_age` of the current time (in either direction, to allow for clock skew),
+/// and its timestamp and sequence number haven't been seen before.
+///
+/// # Examples
+///
+/// ```
+/// use ncr::encryption::ReplayWindow;
+/// use std::time::Duration;
+///
+/// let mut window = ReplayWindow::new(Duration::from_secs(60));
+/// let now = 1_700_000_000_000;
+///
+/// assert!(window.accept(now, 1, now).is_ok());
+/// // Duplicate.
+/// assert!(window.accept(now, 1, now + 500).is_err());
+/// // Stale.
+/// assert!(window.accept(now - 120_000, 2, now).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReplayWindow {
+    max_age: u64,
+    seen: HashSet<(u64, u32)>,
+}
+
+impl ReplayWindow {
+    /// Create a window accepting messages up to `max_age` old.
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age: max_age.as_millis() as u64,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Check a message, and remember it if accepted.
+    ///
+    /// All times are in milliseconds since the unix epoch.
+    ///
+    /// # Error
+    ///
+    /// This return [NcrError::ReplayError] if the message is stale or was already seen.
+    pub fn accept(&mut self, timestamp: u64, sequence: u32, now: u64) -> Result<(), NcrError> {
+        if timestamp.abs_diff(now) > self.max_age {
+            return Err(NcrError::ReplayError);
+        }
+
+        // Messages outside of the window are rejected anyway, there's no need to remember them.
+        let max_age = self.max_age;
+        self.seen.retain(|(seen, _)| seen.abs_diff(now) <= max_age);
+
+        if !self.seen.insert((timestamp, sequence)) {
+            return Err(NcrError::ReplayError);
+        }
+
+        Ok(())
+    }
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
@@ -0,0 +1,160 @@
+use std::num::Wrapping;
+
+/// Multiplier
+const A: Wrapping<i64> = Wrapping(0x5DEECE66D);
+/// Increment
+const C: Wrapping<i64> = Wrapping(0xB);
+/// Modulus
+const M: Wrapping<i64> = Wrapping((1 << 48) - 1);
+
+/// A bit-exact implementation of `java.util.Random`.
+///
+/// The mod (and Minecraft) use it in places where the output must match, such as the aes/cfb8 IV.
+/// Every method returns exactly what the Java method of the same name returns for the same seed.
+///
+/// `nextGaussian` isn't implemented, as it depends on `StrictMath.log`.
+///
+/// # Examples
+///
+/// ```
+/// use ncr::JavaRandom;
+///
+/// // new Random(42)
+/// let mut random = JavaRandom::new(42);
+///
+/// assert_eq!(random.next_int(), -1170105035);
+/// assert_eq!(random.next_int_bounded(10), 3);
+/// assert_eq!(random.next_int_bounded(16), 10);
+/// assert_eq!(random.next_long(), 884324181205335268);
+/// assert_eq!(random.next_double(), 0.9420735430282128);
+/// assert_eq!(random.next_float(), 0.70771056);
+/// assert!(random.next_boolean());
+///
+/// let mut bytes = [0u8; 6];
+/// random.next_bytes(&mut bytes);
+/// assert_eq!(bytes, [-102i8, 12, 97, 23, -67, 103].map(|byte| byte as u8));
+///
+/// random.set_seed(-1);
+/// assert_eq!(random.next_int(), 1155099827);
+/// ```
+///
+/// ## The aes/cfb8 IV
+///
+/// ```
+/// use ncr::JavaRandom;
+///
+/// // new Random(nonce).nextBytes(iv)
+/// let mut iv = [0u8; 16];
+/// JavaRandom::new(0x0102030405060708).next_bytes(&mut iv);
+///
+/// assert_eq!(
+///     iv,
+///     [-98i8, 8, 86, -58, 50, 126, -54, -32, -29, 4, -27, 14, 94, -24, -49, -83].map(|byte| byte as u8)
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaRandom {
+    seed: Wrapping<i64>,
+}
+
+impl JavaRandom {
+    /// Create a generator with the given seed, like `new Random(seed)`.
+    #[inline]
+    pub fn new(seed: i64) -> Self {
+        Self {
+            seed: (Wrapping(seed) ^ A) & M,
+        }
+    }
+
+    /// Reset the seed, like `setSeed(seed)`.
+    #[inline]
+    pub fn set_seed(&mut self, seed: i64) {
+        *self = Self::new(seed);
+    }
+
+    /// Generate the next `bits` (at most 32) random bits, like `next(bits)`.
+    #[inline]
+    pub fn next(&mut self, bits: u32) -> i32 {
+        debug_assert!(bits <= 32);
+
+        self.seed = (self.seed * A + C) & M;
+
+        (self.seed.0 >> (48 - bits)) as i32
+    }
+
+    /// Generate a random `int`, like `nextInt()`.
+    #[inline]
+    pub fn next_int(&mut self) -> i32 {
+        self.next(32)
+    }
+
+    /// Generate a random `int` between 0 (inclusive) and `bound` (exclusive), like `nextInt(bound)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` isn't positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ncr::JavaRandom;
+    ///
+    /// let mut random = JavaRandom::new(0);
+    /// let values: Vec<i32> = (0..5).map(|_| random.next_int_bounded(1_000_000_007)).collect();
+    ///
+    /// assert_eq!(values, [569741353, 785505941, 516548029, 302116440, 368843508]);
+    /// ```
+    pub fn next_int_bounded(&mut self, bound: i32) -> i32 {
+        assert!(bound > 0, "bound must be positive");
+
+        let mut r = self.next(31);
+        let m = bound - 1;
+
+        if bound & m == 0 {
+            // Power of two, take the high bits.
+            ((bound as i64 * r as i64) >> 31) as i32
+        } else {
+            // Reject the values which would make the result biased.
+            let mut u = r;
+            loop {
+                r = u % bound;
+                if u.wrapping_sub(r).wrapping_add(m) >= 0 {
+                    return r;
+                }
+                u = self.next(31);
+            }
+        }
+    }
+
+    /// Generate a random `long`, like `nextLong()`.
+    #[inline]
+    pub fn next_long(&mut self) -> i64 {
+        ((self.next(32) as i64) << 32).wrapping_add(self.next(32) as i64)
+    }
+
+    /// Generate a random `boolean`, like `nextBoolean()`.
+    #[inline]
+    pub fn next_boolean(&mut self) -> bool {
+        self.next(1) != 0
+    }
+
+    /// Generate a random `float` between 0 (inclusive) and 1 (exclusive), like `nextFloat()`.
+    #[inline]
+    pub fn next_float(&mut self) -> f32 {
+        self.next(24) as f32 / (1 << 24) as f32
+    }
+
+    /// Generate a random `double` between 0 (inclusive) and 1 (exclusive), like `nextDouble()`.
+    #[inline]
+    pub fn next_double(&mut self) -> f64 {
+        (((self.next(26) as i64) << 27) + self.next(27) as i64) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Fill `bytes` with random bytes, like `nextBytes(bytes)`.
+    pub fn next_bytes(&mut self, bytes: &mut [u8]) {
+        for chunk in bytes.chunks_mut(4) {
+            let value = self.next_int().to_le_bytes();
+            chunk.copy_from_slice(&value[..chunk.len()]);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Establish a shared key over public chat, using X25519.
+//!
+//! 1. Both players create a [KeyExchange] and send its commitment ([KeyExchange::commitment]) in chat,
+//!    encoded with any chat-safe encoding.
+//! 2. After receiving the other player's commitment, each player calls [KeyExchange::reveal] with it,
+//!    and sends the returned public key in chat.
+//! 3. Both players call [KeyExchange::finish] with the other player's public key, which gives the same [SharedKey].
+//! 4. Both players compare the short authentication string ([SharedKey::sas]) over another channel (voice chat, in person...).
+//!    If it doesn't match, someone (a server or a proxy) replaced the public keys, and the key must not be used.
+//!
+//! The commitment is the SHA-256 hash of the public key. As public keys are only revealed after both commitments are sent,
+//! someone replacing the keys must choose their own keys before seeing the players' keys,
+//! so they can't search for keys giving matching short authentication strings.
+//! Their only chance is a random match, one in a million (the short authentication string has 6 digits).
+//!
+//! The aes key and the short authentication string are derived from the shared secret with HKDF-SHA256,
+//! salted with both public keys.
+//!
+//! # Examples
+//!
+//! ```
+//! use ncr::{encoding::Base64rEncoding, key_exchange::KeyExchange};
+//!
+//! let mut alice = KeyExchange::new();
+//! let mut bob = KeyExchange::new();
+//!
+//! // Sent in chat.
+//! let alice_commitment = alice.commitment::<Base64rEncoding>();
+//! let bob_commitment = bob.commitment::<Base64rEncoding>();
+//!
+//! // Sent in chat, once the other commitment is received.
+//! let alice_public = alice.reveal::<Base64rEncoding>(&bob_commitment).unwrap();
+//! let bob_public = bob.reveal::<Base64rEncoding>(&alice_commitment).unwrap();
+//!
+//! let alice_shared = alice.finish::<Base64rEncoding>(&bob_public).unwrap();
+//! let bob_shared = bob.finish::<Base64rEncoding>(&alice_public).unwrap();
+//!
+//! assert_eq!(alice_shared.sas(), bob_shared.sas());
+//! assert_eq!(alice_shared.key(), bob_shared.key());
+//! ```
+//!
+//! ## Replaced public key
+//!
+//! ```
+//! use ncr::{encoding::Base64rEncoding, key_exchange::KeyExchange};
+//!
+//! let mut alice = KeyExchange::new();
+//! let mut bob = KeyExchange::new();
+//! let mut mallory = KeyExchange::new();
+//!
+//! alice.reveal::<Base64rEncoding>(&bob.commitment::<Base64rEncoding>()).unwrap();
+//! let mallory_public = mallory.reveal::<Base64rEncoding>(&bob.commitment::<Base64rEncoding>()).unwrap();
+//!
+//! // The public key doesn't match Bob's commitment.
+//! assert!(alice.finish::<Base64rEncoding>(&mallory_public).is_err());
+//! ```
+
+use hkdf::Hkdf;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+use crate::{encoding::Encoding, AesKey, NcrError};
+
+const KEY_INFO: &[u8] = b"ncr x25519 aes key";
+const SAS_INFO: &[u8] = b"ncr x25519 sas";
+
+/// One side of a key exchange.
+///
+/// The secret is ephemeral, a new exchange must be created for every key.
+pub struct KeyExchange {
+    secret: EphemeralSecret,
+    public: PublicKey,
+    their_commitment: Option<[u8; 32]>,
+}
+
+impl KeyExchange {
+    /// Generate a new key pair.
+    pub fn new() -> Self {
+        let secret = EphemeralSecret::random_from_rng(rand::thread_rng());
+        let public = PublicKey::from(&secret);
+
+        Self {
+            secret,
+            public,
+            their_commitment: None,
+        }
+    }
+
+    /// Encode the commitment to the public key, to be sent in chat first.
+    pub fn commitment<E: Encoding>(&self) -> String {
+        E::encode(&Sha256::digest(self.public.as_bytes()))
+    }
+
+    /// Record the other player's commitment, and encode the public key, to be sent in chat.
+    ///
+    /// The public key must only be sent after receiving the other player's commitment.
+    ///
+    /// # Error
+    ///
+    /// This return [NcrError::DecodeError] if the commitment can't be decoded or isn't 32 bytes long.
+    pub fn reveal<E: Encoding>(&mut self, their_commitment: &str) -> Result<String, NcrError> {
+        let their_commitment: [u8; 32] = E::decode(their_commitment)?
+            .try_into()
+            .map_err(|_| NcrError::DecodeError)?;
+
+        self.their_commitment = Some(their_commitment);
+
+        Ok(E::encode(self.public.as_bytes()))
+    }
+
+    /// Derive the shared key from the other player's public key.
+    ///
+    /// # Error
+    ///
+    /// This return [NcrError::DecodeError] if the public key can't be decoded or isn't 32 bytes long,
+    /// and [NcrError::DecryptError] if [KeyExchange::reveal] wasn't called, the public key doesn't match the commitment,
+    /// or the public key is a low order point (which would give a known shared secret).
+    pub fn finish<E: Encoding>(self, their_public: &str) -> Result<SharedKey, NcrError> {
+        let their_public: [u8; 32] = E::decode(their_public)?
+            .try_into()
+            .map_err(|_| NcrError::DecodeError)?;
+
+        if self.their_commitment != Some(Sha256::digest(their_public).into()) {
+            return Err(NcrError::DecryptError);
+        }
+        let ours = *self.public.as_bytes();
+        let shared = self.secret.diffie_hellman(&PublicKey::from(their_public));
+
+        if !shared.was_contributory() {
+            return Err(NcrError::DecryptError);
+        }
+
+        // Sort the public keys, so both sides use the same salt.
+        let mut salt = [0u8; 64];
+        let (first, second) = if ours <= their_public {
+            (ours, their_public)
+        } else {
+            (their_public, ours)
+        };
+        salt[..32].copy_from_slice(&first);
+        salt[32..].copy_from_slice(&second);
+
+        let hkdf = Hkdf::<Sha256>::new(Some(&salt), shared.as_bytes());
+
+        let mut key = [0u8; 16];
+        hkdf.expand(KEY_INFO, &mut key).unwrap();
+
+        let mut sas = [0u8; 4];
+        hkdf.expand(SAS_INFO, &mut sas).unwrap();
+
+        Ok(SharedKey {
+            key: AesKey::from(key),
+            sas: u32::from_be_bytes(sas) % 1_000_000,
+        })
+    }
+}
+
+impl Default for KeyExchange {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for KeyExchange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyExchange")
+            .field("public", self.public.as_bytes())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The result of a key exchange.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedKey {
+    key: AesKey,
+    sas: u32,
+}
+
+impl SharedKey {
+    /// Returns the shared aes key.
+    #[inline]
+    pub fn key(&self) -> &AesKey {
+        &self.key
+    }
+
+    /// Consumes the result, returns the shared aes key.
+    #[inline]
+    pub fn into_key(self) -> AesKey {
+        self.key
+    }
+
+    /// Returns the short authentication string, 6 digits separated by a space (such as `"042 917"`).
+    ///
+    /// Both players must compare it over another channel before using the key.
+    pub fn sas(&self) -> String {
+        format!("{:03} {:03}", self.sas / 1000, self.sas % 1000)
+    }
+}
@@ -0,0 +1,321 @@
+//! Store keys on disk, encrypted with a passphrase.
+//!
+//! The file key is derived from the passphrase with Argon2id, and the keys are encrypted with aes-256/gcm.
+//! The Argon2id parameters are stored in the file, so they can be raised later without breaking old files.
+//!
+//! # Examples
+//!
+//! ```
+//! use ncr::{key_file, AesKey};
+//!
+//! let keys = vec![AesKey::gen_random_key(), AesKey::gen_from_passphrase(b"secret")];
+//! let path = std::env::temp_dir().join("ncr-doctest.keys");
+//!
+//! key_file::save(&path, &keys, b"correct horse battery staple").unwrap();
+//!
+//! assert_eq!(key_file::load(&path, b"correct horse battery staple").unwrap(), keys);
+//! assert_eq!(
+//!     key_file::load(&path, b"wrong passphrase"),
+//!     Err(key_file::KeyFileError::Passphrase)
+//! );
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use aes_gcm::{
+    aead::{Aead, Payload},
+    Aes256Gcm, KeyInit,
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::Rng;
+use std::{fmt, fs, io, path::Path};
+
+use crate::AesKey;
+
+const MAGIC: &[u8; 4] = b"NCRK";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 4 + 1 + 12 + 16 + 12;
+
+// Key file:
+// This diagram shows the bytes stored in the file.
+//
+// |   4   -    1    -   4    -   4    -   4    -  16  -  12  -     Var      -  16   | (bytes)
+// | Magic | Version | m_cost | t_cost | p_cost | Salt |  IV  |  Ciphertext  |  Tag  |
+// |---------------------------------------------------------------------------------|
+//
+// Where:
+//     Magic is "NCRK".
+//     Version is the format version (currently 1).
+//     m_cost, t_cost and p_cost are the Argon2id parameters (big endian): memory in KiB, iterations and parallelism.
+//     Salt is the Argon2id salt.
+//     IV (or Nonce) is used for encryption.
+//     Ciphertext is the keys (16 bytes each) after encryption.
+//     Tag is the GCM Authorization Tag.
+//
+// Everything before the ciphertext is authenticated, so the parameters can't be tampered with.
+
+/// The Argon2id parameters used to derive the file key.
+///
+/// The parameters are read from the file, so they are limited to keep a crafted file from exhausting memory or time:
+/// at most 1 GiB of memory, 64 iterations and 16 degrees of parallelism.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory size, in KiB.
+    pub memory: u32,
+    /// Number of iterations.
+    pub iterations: u32,
+    /// Degree of parallelism.
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// Maximum memory size, in KiB (1 GiB).
+    pub const MAX_MEMORY: u32 = 1 << 20;
+    /// Maximum number of iterations.
+    pub const MAX_ITERATIONS: u32 = 64;
+    /// Maximum degree of parallelism.
+    pub const MAX_PARALLELISM: u32 = 16;
+
+    fn argon2(&self) -> Result<Argon2<'static>, KeyFileError> {
+        if self.memory > Self::MAX_MEMORY
+            || self.iterations > Self::MAX_ITERATIONS
+            || self.parallelism > Self::MAX_PARALLELISM
+        {
+            return Err(KeyFileError::Params);
+        }
+
+        let params = Params::new(self.memory, self.iterations, self.parallelism, Some(32))
+            .map_err(|_| KeyFileError::Params)?;
+
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
+
+impl Default for KdfParams {
+    /// The parameters recommended by OWASP (19 MiB of memory, 2 iterations, 1 degree of parallelism).
+    #[inline]
+    fn default() -> Self {
+        Self {
+            memory: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// Encrypt keys into the key file format, with the default [KdfParams].
+#[inline]
+pub fn encrypt(keys: &[AesKey], passphrase: &[u8]) -> Vec<u8> {
+    encrypt_with(keys, passphrase, &KdfParams::default()).unwrap()
+}
+
+/// Encrypt keys into the key file format.
+///
+/// # Error
+///
+/// This return [KeyFileError::Params] if the parameters are above the limits of [KdfParams], or rejected by Argon2id.
+pub fn encrypt_with(
+    keys: &[AesKey],
+    passphrase: &[u8],
+    params: &KdfParams,
+) -> Result<Vec<u8>, KeyFileError> {
+    let mut rng = rand::thread_rng();
+    let salt = rng.gen::<[u8; 16]>();
+    let iv = rng.gen::<[u8; 12]>();
+
+    let mut output = Vec::with_capacity(HEADER_LEN + keys.len() * 16 + 16);
+    output.extend_from_slice(MAGIC);
+    output.push(VERSION);
+    output.extend_from_slice(&params.memory.to_be_bytes());
+    output.extend_from_slice(&params.iterations.to_be_bytes());
+    output.extend_from_slice(&params.parallelism.to_be_bytes());
+    output.extend_from_slice(&salt);
+    output.extend_from_slice(&iv);
+
+    let plaintext: Vec<u8> = keys.iter().flat_map(|key| *key.as_ref()).collect();
+
+    let cipher = derive_cipher(passphrase, &salt, params)?;
+    let ciphertext = cipher
+        .encrypt(
+            &iv.into(),
+            Payload {
+                msg: &plaintext,
+                aad: &output,
+            },
+        )
+        .unwrap();
+
+    output.extend_from_slice(&ciphertext);
+
+    Ok(output)
+}
+
+/// Decrypt keys from the key file format.
+///
+/// # Error
+///
+/// This return a error if the data isn't a key file, it's version isn't supported,
+/// it's parameters are above the limits of [KdfParams], or the passphrase is wrong (or the file is corrupted).
+///
+/// # Examples
+///
+/// ```
+/// use ncr::{
+///     key_file::{self, KeyFileError},
+///     AesKey,
+/// };
+///
+/// let mut data = key_file::encrypt(&[AesKey::gen_random_key()], b"passphrase");
+///
+/// // A file asking for 4 TiB of memory.
+/// data[5..9].copy_from_slice(&u32::MAX.to_be_bytes());
+/// assert_eq!(key_file::decrypt(&data, b"passphrase"), Err(KeyFileError::Params));
+/// ```
+pub fn decrypt(data: &[u8], passphrase: &[u8]) -> Result<Vec<AesKey>, KeyFileError> {
+    if data.len() < 5 || &data[..4] != MAGIC {
+        return Err(KeyFileError::Format);
+    }
+
+    if data[4] != VERSION {
+        return Err(KeyFileError::Version(data[4]));
+    }
+
+    if data.len() < HEADER_LEN + 16 {
+        return Err(KeyFileError::Format);
+    }
+
+    let read_u32 = |offset: usize| u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap());
+    let params = KdfParams {
+        memory: read_u32(5),
+        iterations: read_u32(9),
+        parallelism: read_u32(13),
+    };
+    let salt: [u8; 16] = data[17..33].try_into().unwrap();
+    let iv: [u8; 12] = data[33..45].try_into().unwrap();
+
+    let cipher = derive_cipher(passphrase, &salt, &params)?;
+    let plaintext = cipher
+        .decrypt(
+            &iv.into(),
+            Payload {
+                msg: &data[HEADER_LEN..],
+                aad: &data[..HEADER_LEN],
+            },
+        )
+        .map_err(|_| KeyFileError::Passphrase)?;
+
+    if plaintext.len() % 16 != 0 {
+        return Err(KeyFileError::Format);
+    }
+
+    Ok(plaintext
+        .chunks_exact(16)
+        .map(|key| AesKey::from(<[u8; 16]>::try_from(key).unwrap()))
+        .collect())
+}
+
+/// Save keys to a file, with the default [KdfParams].
+///
+/// The keys are written to a temporary file which then replaces the file, so a failed save keeps the previous file intact.
+/// On unix, the file has `0600` permissions (only readable by the owner), even when replacing an existing file.
+///
+/// # Error
+///
+/// This return [KeyFileError::Io] if the file can't be written.
+pub fn save<P: AsRef<Path>>(
+    path: P,
+    keys: &[AesKey],
+    passphrase: &[u8],
+) -> Result<(), KeyFileError> {
+    write_file(path.as_ref(), &encrypt(keys, passphrase))
+}
+
+/// Load keys from a file.
+///
+/// # Error
+///
+/// This return [KeyFileError::Io] if the file can't be read, or the errors of [decrypt].
+pub fn load<P: AsRef<Path>>(path: P, passphrase: &[u8]) -> Result<Vec<AesKey>, KeyFileError> {
+    decrypt(&fs::read(path)?, passphrase)
+}
+
+fn derive_cipher(
+    passphrase: &[u8],
+    salt: &[u8; 16],
+    params: &KdfParams,
+) -> Result<Aes256Gcm, KeyFileError> {
+    let mut key = [0u8; 32];
+    params
+        .argon2()?
+        .hash_password_into(passphrase, salt, &mut key)
+        .map_err(|_| KeyFileError::Params)?;
+
+    Ok(Aes256Gcm::new(&key.into()))
+}
+
+/// Write to a temporary file next to `path`, then rename it over `path`,
+/// so an interrupted write never leaves a truncated key file.
+fn write_file(path: &Path, data: &[u8]) -> Result<(), KeyFileError> {
+    use io::Write;
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(format!(".{:016x}.tmp", rand::thread_rng().gen::<u64>()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let result = options.open(&temp_path).and_then(|mut file| {
+        file.write_all(data)?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&temp_path, path)
+    });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    Ok(result?)
+}
+
+/// This represents all errors that can happen when loading or saving a key file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyFileError {
+    /// The file can't be read or written.
+    Io(io::ErrorKind),
+    /// The data isn't a key file.
+    Format,
+    /// The key file version isn't supported.
+    Version(u8),
+    /// The Argon2id parameters are invalid.
+    Params,
+    /// The passphrase is wrong, or the file is corrupted.
+    Passphrase,
+}
+
+impl From<io::Error> for KeyFileError {
+    #[inline]
+    fn from(value: io::Error) -> Self {
+        Self::Io(value.kind())
+    }
+}
+
+impl fmt::Display for KeyFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyFileError::Io(kind) => write!(f, "Io error: {kind}"),
+            KeyFileError::Format => write!(f, "Not a key file"),
+            KeyFileError::Version(version) => write!(f, "Unsupported key file version {version}"),
+            KeyFileError::Params => write!(f, "Invalid key derivation parameters"),
+            KeyFileError::Passphrase => write!(f, "Wrong passphrase or corrupted key file"),
+        }
+    }
+}
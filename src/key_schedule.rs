@@ -0,0 +1,208 @@
+//! Rotate keys over time, without redistributing them.
+//!
+//! A [KeySchedule] derives a new [AesKey] for every epoch (a week by default) from a master secret,
+//! with HKDF-SHA256 and the date the epoch starts (in UTC) as label.
+//! Everyone holding the master secret gets the same keys, and a leaked key only exposes the messages of its epoch.
+//!
+//! When decrypting, the keys of the previous epochs (1 by default) are tried too,
+//! so messages sent just before the rotation stay readable for a grace period.
+//! A key is accepted when the decrypted text starts with the "#%" header.
+//!
+//! # Examples
+//!
+//! ```
+//! use ncr::{
+//!     encoding::Base64rEncoding,
+//!     encryption::Cfb8Encryption,
+//!     key_schedule::KeySchedule,
+//! };
+//!
+//! let schedule = KeySchedule::new(b"our clan master secret");
+//!
+//! let encrypted = schedule.encrypt::<Cfb8Encryption<Base64rEncoding>>("#%Hello, world!").unwrap();
+//! let decrypted = schedule.decrypt::<Cfb8Encryption<Base64rEncoding>>(&encrypted).unwrap();
+//!
+//! assert_eq!(decrypted, "#%Hello, world!");
+//! ```
+//!
+//! ## Grace period
+//!
+//! ```
+//! use std::time::{Duration, UNIX_EPOCH};
+//!
+//! use ncr::{
+//!     encoding::Base64rEncoding,
+//!     encryption::{Cfb8Encryption, Encryption},
+//!     key_schedule::KeySchedule,
+//! };
+//!
+//! let schedule = KeySchedule::new(b"our clan master secret");
+//!
+//! // 2023-06-05 is in the epoch starting on 2023-06-01 (epochs start on thursdays, like the unix epoch).
+//! let sent = UNIX_EPOCH + Duration::from_secs(1_685_966_400);
+//! let epoch = schedule.epoch_at(sent);
+//! assert_eq!(schedule.label(epoch), "2023-06-01");
+//!
+//! let key = schedule.key(epoch);
+//! let encrypted = Cfb8Encryption::<Base64rEncoding>::encrypt("#%Hello, world!", &key).unwrap();
+//!
+//! // Still readable one week later...
+//! let week = Duration::from_secs(7 * 24 * 60 * 60);
+//! assert!(schedule.decrypt_at::<Cfb8Encryption<Base64rEncoding>>(&encrypted, sent + week).is_ok());
+//!
+//! // ...but not two weeks later.
+//! assert!(schedule.decrypt_at::<Cfb8Encryption<Base64rEncoding>>(&encrypted, sent + week * 2).is_err());
+//! ```
+
+use hkdf::Hkdf;
+use sha2::Sha256;
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{encryption::Encryption, utils::trim_header, AesKey, NcrError};
+
+const SALT: &[u8] = b"ncr key schedule";
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Derives keys from a master secret and the current time.
+#[derive(Clone)]
+pub struct KeySchedule {
+    hkdf: Hkdf<Sha256>,
+    period: u32,
+    grace: u32,
+}
+
+impl KeySchedule {
+    /// Create a schedule rotating the key every week, accepting the previous key when decrypting.
+    pub fn new(master: &[u8]) -> Self {
+        Self {
+            hkdf: Hkdf::new(Some(SALT), master),
+            period: 7,
+            grace: 1,
+        }
+    }
+
+    /// Set the length of an epoch, in days.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `days` is zero.
+    pub fn with_period(mut self, days: u32) -> Self {
+        assert!(days > 0, "period must be at least one day");
+
+        self.period = days;
+        self
+    }
+
+    /// Set how many previous epochs are tried when decrypting.
+    pub fn with_grace(mut self, epochs: u32) -> Self {
+        self.grace = epochs;
+        self
+    }
+
+    /// Returns the epoch containing the given time.
+    pub fn epoch_at(&self, time: SystemTime) -> u64 {
+        let seconds = time
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+
+        seconds / SECONDS_PER_DAY / self.period as u64
+    }
+
+    /// Returns the current epoch.
+    #[inline]
+    pub fn current_epoch(&self) -> u64 {
+        self.epoch_at(SystemTime::now())
+    }
+
+    /// Returns the label of an epoch, the date it starts (in UTC) formatted as `YYYY-MM-DD`.
+    pub fn label(&self, epoch: u64) -> String {
+        let (year, month, day) = civil_from_days(epoch * self.period as u64);
+
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+
+    /// Derive the key of an epoch.
+    pub fn key(&self, epoch: u64) -> AesKey {
+        let info = format!("ncr epoch {}", self.label(epoch));
+
+        let mut key = [0u8; 16];
+        self.hkdf.expand(info.as_bytes(), &mut key).unwrap();
+
+        AesKey::from(key)
+    }
+
+    /// Derive the key of the current epoch.
+    #[inline]
+    pub fn current_key(&self) -> AesKey {
+        self.key(self.current_epoch())
+    }
+
+    /// Encrypt a given text with the key of the current epoch.
+    pub fn encrypt<E: Encryption<KeyType = AesKey>>(
+        &self,
+        plaintext: &str,
+    ) -> Result<String, E::EncryptError> {
+        E::encrypt(plaintext, &self.current_key())
+    }
+
+    /// Decrypt a given text with the keys of the current and previous epochs.
+    ///
+    /// # Error
+    ///
+    /// This return [NcrError::DecryptError] if no key gives a text starting with the "#%" header.
+    #[inline]
+    pub fn decrypt<E: Encryption<KeyType = AesKey>>(
+        &self,
+        ciphertext: &str,
+    ) -> Result<String, NcrError> {
+        self.decrypt_at::<E>(ciphertext, SystemTime::now())
+    }
+
+    /// Decrypt a given text with the keys of the epoch containing `time` and the previous epochs.
+    ///
+    /// # Error
+    ///
+    /// This return [NcrError::DecryptError] if no key gives a text starting with the "#%" header.
+    pub fn decrypt_at<E: Encryption<KeyType = AesKey>>(
+        &self,
+        ciphertext: &str,
+        time: SystemTime,
+    ) -> Result<String, NcrError> {
+        let current = self.epoch_at(time);
+        let oldest = current.saturating_sub(self.grace as u64);
+
+        (oldest..=current)
+            .rev()
+            .filter_map(|epoch| E::decrypt(ciphertext, &self.key(epoch)).ok())
+            .find(|plaintext| trim_header(plaintext).is_ok())
+            .ok_or(NcrError::DecryptError)
+    }
+}
+
+impl fmt::Debug for KeySchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeySchedule")
+            .field("period", &self.period)
+            .field("grace", &self.grace)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) date. [Source](http://howardhinnant.github.io/date_algorithms.html#civil_from_days)
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    (year, month, day)
+}
@@ -52,12 +52,21 @@
 //!
 //! # Features
 //!
-//! Current there are 4 feature flags.
+//! Current there are 13 feature flags.
 //!
 //!  - `passphrase` (default): Enable key generation from passphrase.
 //!  - `cfb8`: Enable aes/cfb8 encryption.
 //!  - `ecb`: Enable aes/ecb encryption.
 //!  - `gcm`: Enable aes/gcm encryption.
+//!  - `rayon`: Enable parallel batch decryption.
+//!  - `codec`: Enable the [tokio-util](https://docs.rs/tokio-util) codec for chat streams.
+//!  - `component`: Enable decryption of Minecraft json text components.
+//!  - `compression`: Enable compression before encryption.
+//!  - `envelope`: Enable the self-describing envelope format (enables `cfb8` and `gcm`).
+//!  - `key-exchange`: Enable in-chat key exchange using X25519.
+//!  - `key-schedule`: Enable time based key rotation from a master secret.
+//!  - `mnemonic`: Enable exporting and importing keys as words.
+//!  - `key-file`: Enable storing keys in a passphrase protected file.
 //!
 //! # How NCR encrypt chat messages
 //! 1. Two characters `#%` will be prepended to every message.
@@ -69,13 +78,31 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
 mod aes_key;
+pub mod audit;
+pub mod chat;
+#[cfg(feature = "codec")]
+pub mod codec;
+#[cfg(feature = "component")]
+pub mod component;
 pub mod encoding;
 pub mod encryption;
+mod java_random;
+#[cfg(feature = "key-exchange")]
+pub mod key_exchange;
+#[cfg(feature = "key-file")]
+pub mod key_file;
+#[cfg(feature = "key-schedule")]
+pub mod key_schedule;
+pub mod message;
 pub mod utils;
+pub mod validation;
 
 use std::fmt;
 
 pub use aes_key::AesKey;
+#[cfg(feature = "mnemonic")]
+pub use aes_key::MnemonicError;
+pub use java_random::JavaRandom;
 
 /// This represents all errors that can happen in this crate.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -84,6 +111,7 @@ pub enum NcrError {
     DecryptError,
     DecodeError,
     HeaderError,
+    ReplayError,
 }
 
 impl fmt::Display for NcrError {
@@ -96,6 +124,7 @@ impl fmt::Display for NcrError {
                 NcrError::DecryptError => "Decrypt error",
                 NcrError::DecodeError => "Decode error",
                 NcrError::HeaderError => "Header error",
+                NcrError::ReplayError => "Replay error",
             }
         )
     }
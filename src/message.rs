@@ -0,0 +1,165 @@
+//! Typed messages, with automatic header handling.
+//!
+//! [seal] prepends the header and encrypts a [Plaintext], [open] decrypts a [Ciphertext] and checks the header,
+//! so the header can't be forgotten on either side.
+//! Forks using a different header than "#%" can use [Header] directly.
+//!
+//! # Examples
+//!
+//! ```
+//! use ncr::{
+//!     encoding::Base64rEncoding,
+//!     encryption::Cfb8Cipher,
+//!     message::{open, seal, Ciphertext, Header, Plaintext},
+//!     AesKey,
+//! };
+//!
+//! let cipher = Cfb8Cipher::<Base64rEncoding>::new(&AesKey::gen_from_passphrase(b"secret"));
+//!
+//! let ciphertext = seal(&cipher, &Plaintext::from("I love Minecraft!")).unwrap();
+//! assert_eq!(open(&cipher, &ciphertext).unwrap().as_str(), "I love Minecraft!");
+//!
+//! let ciphertext = Ciphertext::from(r#"%[2_0»³"!7).«?;!.$¥`¶:8~667ª¸[¬)¢+¤^"#);
+//! assert_eq!(open(&cipher, &ciphertext).unwrap().as_str(), "I love Minecraft!");
+//!
+//! // A fork with a different header.
+//! let header = Header::new("$$");
+//! let ciphertext = header.seal(&cipher, &Plaintext::from("Hello")).unwrap();
+//!
+//! assert_eq!(header.open(&cipher, &ciphertext).unwrap().as_str(), "Hello");
+//! assert!(open(&cipher, &ciphertext).is_err());
+//! ```
+
+use std::{borrow::Cow, fmt};
+
+use crate::{encryption::Cipher, NcrError};
+
+/// A message before encryption, without the header.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Plaintext(String);
+
+/// A message after encryption, as sent in chat.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Ciphertext(String);
+
+macro_rules! impl_text {
+    ($name:ident) => {
+        impl $name {
+            /// Returns the text.
+            #[inline]
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// Consumes the message, returns the text.
+            #[inline]
+            pub fn into_string(self) -> String {
+                self.0
+            }
+        }
+
+        impl From<String> for $name {
+            #[inline]
+            fn from(value: String) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<&str> for $name {
+            #[inline]
+            fn from(value: &str) -> Self {
+                Self(value.to_owned())
+            }
+        }
+
+        impl AsRef<str> for $name {
+            #[inline]
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+impl_text!(Plaintext);
+impl_text!(Ciphertext);
+
+/// The marker prepended to every message before encryption.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Header(Cow<'static, str>);
+
+impl Header {
+    /// The "#%" header used by No Chat Reports.
+    pub const DEFAULT: Header = Header(Cow::Borrowed("#%"));
+
+    /// Create a header with a custom marker.
+    pub fn new(marker: impl Into<String>) -> Self {
+        Self(Cow::Owned(marker.into()))
+    }
+
+    /// Returns the marker.
+    #[inline]
+    pub fn marker(&self) -> &str {
+        &self.0
+    }
+
+    /// Prepend the header, then encrypt.
+    pub fn seal<C: Cipher>(
+        &self,
+        cipher: &C,
+        plaintext: &Plaintext,
+    ) -> Result<Ciphertext, NcrError> {
+        let text = self.marker().to_owned() + plaintext.as_str();
+
+        cipher.encrypt(&text).map(Ciphertext)
+    }
+
+    /// Decrypt, then check and remove the header.
+    ///
+    /// # Error
+    ///
+    /// This return [NcrError::HeaderError] if the decrypted text doesn't start with the header.
+    pub fn open<C: Cipher>(
+        &self,
+        cipher: &C,
+        ciphertext: &Ciphertext,
+    ) -> Result<Plaintext, NcrError> {
+        let mut text = cipher.decrypt(ciphertext.as_str())?;
+
+        if !text.starts_with(self.marker()) {
+            return Err(NcrError::HeaderError);
+        }
+        text.drain(..self.marker().len());
+
+        Ok(Plaintext(text))
+    }
+}
+
+impl Default for Header {
+    #[inline]
+    fn default() -> Self {
+        Header::DEFAULT
+    }
+}
+
+/// Prepend the "#%" header, then encrypt.
+///
+/// See [Header::seal].
+#[inline]
+pub fn seal<C: Cipher>(cipher: &C, plaintext: &Plaintext) -> Result<Ciphertext, NcrError> {
+    Header::DEFAULT.seal(cipher, plaintext)
+}
+
+/// Decrypt, then check and remove the "#%" header.
+///
+/// See [Header::open].
+#[inline]
+pub fn open<C: Cipher>(cipher: &C, ciphertext: &Ciphertext) -> Result<Plaintext, NcrError> {
+    Header::DEFAULT.open(cipher, ciphertext)
+}
@@ -1,8 +1,12 @@
 //! Some common utility functions.
 
+use std::borrow::Cow;
+
 use crate::NcrError;
 
 /// Append "#%" before a text.
+///
+/// [message::seal](crate::message::seal) does this automatically.
 #[inline]
 pub fn prepend_header(text: &str) -> String {
     "#%".to_owned() + text
@@ -10,6 +14,8 @@ pub fn prepend_header(text: &str) -> String {
 
 /// Remove "#%" before a text.
 ///
+/// [message::open](crate::message::open) does this automatically.
+///
 /// # Error
 ///
 /// This return a error if "#%" is not presented before the text.
@@ -21,3 +27,48 @@ pub fn trim_header(text: &str) -> Result<&str, NcrError> {
         Err(NcrError::HeaderError)
     }
 }
+
+/// Remove Minecraft formatting codes and other decoration that servers insert into chat.
+///
+/// This removes:
+/// - Legacy formatting codes (`§` followed by any character), including hex colors (`§x§r§r§g§g§b§b`).
+/// - Zero width characters (`U+200B` to `U+200D`, `U+2060` and `U+FEFF`).
+/// - Leading and trailing whitespace.
+///
+/// None of these characters appear in the output of the encodings, so this never changes a valid ciphertext.
+/// [CaesarEncryption](crate::encryption::CaesarEncryption) replaces `§` with a placeholder, so its ciphertexts are also unaffected.
+///
+/// # Examples
+///
+/// ```
+/// use ncr::utils::strip_formatting;
+///
+/// assert_eq!(strip_formatting("§7Hello, §x§f§f§0§0§0§0world§r!"), "Hello, world!");
+/// assert_eq!(strip_formatting("Hello\u{200B}, world! "), "Hello, world!");
+/// ```
+pub fn strip_formatting(text: &str) -> Cow<'_, str> {
+    let text = text.trim();
+
+    if !text.chars().any(|ch| ch == '§' || is_zero_width(ch)) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut output = String::with_capacity(text.len());
+
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '§' {
+            chars.next();
+        } else if !is_zero_width(ch) {
+            output.push(ch);
+        }
+    }
+
+    // Removing codes may expose more whitespace.
+    Cow::Owned(output.trim().to_owned())
+}
+
+#[inline]
+fn is_zero_width(ch: char) -> bool {
+    matches!(ch, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}')
+}
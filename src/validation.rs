@@ -0,0 +1,223 @@
+//! Check that a message is accepted by Minecraft chat.
+//!
+//! Vanilla Minecraft rejects or alters some characters in chat messages, which breaks decoding on the receiving side:
+//!
+//! - `§`, DEL and control characters (including the C1 controls `U+0080` to `U+009F`) are rejected ([ViolationKind::Forbidden]).
+//! - Whitespace is normalized: leading and trailing whitespace is removed, and runs of whitespace become a single space
+//!   ([ViolationKind::Whitespace]).
+//! - Invisible characters such as the soft hyphen (`U+00AD`) may be removed by servers, proxies or clients
+//!   ([ViolationKind::Invisible]).
+//! - In 1.19.3 and above, `¸` is changed to `×` ([ViolationKind::Replaced]).
+//! - A message starting with `/` is sent as a command ([ViolationKind::Command]).
+//! - A message can't be longer than 256 characters ([ViolationKind::TooLong]).
+//!
+//! # Examples
+//!
+//! ```
+//! use ncr::validation::{validate_chat, ChatVersion, ViolationKind};
+//!
+//! assert_eq!(validate_chat("Hello, world!", ChatVersion::V1_19_3), Ok(()));
+//!
+//! let violations = validate_chat("Hello,\u{AD} §cworld!", ChatVersion::V1_19_3).unwrap_err();
+//! assert_eq!(violations[0].position, 6);
+//! assert_eq!(violations[0].kind, ViolationKind::Invisible);
+//! assert_eq!(violations[1].position, 8);
+//! assert_eq!(violations[1].kind, ViolationKind::Forbidden);
+//! ```
+//!
+//! ## Encodings
+//!
+//! Every character of every encoding is accepted, except for the soft hyphen in [Mc256Encoding](crate::encoding::Mc256Encoding),
+//! and `¸` in [Base64rEncoding](crate::encoding::Base64rEncoding) for 1.19.3 and above.
+//!
+//! ```
+//! use ncr::{
+//!     encoding::*,
+//!     validation::{validate_chat, ChatViolation, ChatVersion, ViolationKind},
+//! };
+//!
+//! fn kinds(text: &str, version: ChatVersion) -> Vec<(char, ViolationKind)> {
+//!     // Check each character on its own, to skip the length limit.
+//!     let mut kinds: Vec<_> = text
+//!         .chars()
+//!         .filter_map(|ch| validate_chat(&ch.to_string(), version).err())
+//!         .flatten()
+//!         .map(|violation| (violation.ch, violation.kind))
+//!         .collect();
+//!     kinds.dedup();
+//!     kinds
+//! }
+//!
+//! // All 256 bytes produce every character of the base64, sus16 and mc256 alphabets (including padding).
+//! let bytes: Vec<u8> = (0..=255).collect();
+//!
+//! // Every 15 bit value, followed by every 7 bit value for the last character.
+//! let mut mc32768 = String::new();
+//! for value in 0..32768u32 {
+//!     mc32768.push_str(&Mc32768Encoding::encode(&[(value >> 7) as u8, (value << 1) as u8 | 1])[..3]);
+//! }
+//! // 14 bytes leave 7 bits for the last character.
+//! for value in 0..128u8 {
+//!     let mut data = [0u8; 14];
+//!     data[13] = value;
+//!     mc32768.push(Mc32768Encoding::encode(&data).chars().last().unwrap());
+//! }
+//! assert_eq!(mc32768.chars().count(), 32768 + 128);
+//!
+//! for version in [ChatVersion::V1_19_2, ChatVersion::V1_19_3] {
+//!     assert_eq!(kinds(&Base64Encoding::encode(&bytes), version), []);
+//!     assert_eq!(kinds(&NewBase64rEncoding::encode(&bytes), version), []);
+//!     assert_eq!(kinds(&Sus16Encoding::encode(&bytes), version), []);
+//!     assert_eq!(kinds(&mc32768, version), []);
+//!     assert_eq!(
+//!         kinds(&Mc256Encoding::encode(&bytes), version),
+//!         [('\u{AD}', ViolationKind::Invisible)]
+//!     );
+//! }
+//!
+//! assert_eq!(kinds(&Base64rEncoding::encode(&bytes), ChatVersion::V1_19_2), []);
+//! assert_eq!(
+//!     kinds(&Base64rEncoding::encode(&bytes), ChatVersion::V1_19_3),
+//!     [('¸', ViolationKind::Replaced)]
+//! );
+//! ```
+
+/// Maximum length of a chat message, in utf-16 code units (like java strings).
+pub const MAX_CHAT_LENGTH: usize = 256;
+
+/// The Minecraft version whose chat rules are used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChatVersion {
+    /// 1.19.2 and below.
+    V1_19_2,
+    /// 1.19.3 and above.
+    V1_19_3,
+}
+
+/// Why a character isn't accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViolationKind {
+    /// The character is rejected by the server (`§`, DEL or a control character).
+    Forbidden,
+    /// The whitespace is removed or collapsed.
+    Whitespace,
+    /// The character is invisible and may be removed in transit.
+    Invisible,
+    /// The character is replaced by another character.
+    Replaced,
+    /// The message starts with `/`, and would be sent as a command.
+    Command,
+    /// The message is too long, starting from this character.
+    TooLong,
+}
+
+/// A character that isn't accepted by Minecraft chat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChatViolation {
+    /// The index of the character (in chars, not bytes).
+    pub position: usize,
+    pub ch: char,
+    pub kind: ViolationKind,
+}
+
+/// Check a message against the vanilla chat rules of a Minecraft version.
+///
+/// See the [module documentation](self).
+///
+/// # Error
+///
+/// This return a error with every offending character, in order.
+pub fn validate_chat(text: &str, version: ChatVersion) -> Result<(), Vec<ChatViolation>> {
+    let mut violations = Vec::new();
+    let mut length = 0;
+
+    let chars: Vec<char> = text.chars().collect();
+
+    for (position, ch) in chars.iter().copied().enumerate() {
+        let mut report = |kind| violations.push(ChatViolation { position, ch, kind });
+
+        if position == 0 && ch == '/' {
+            report(ViolationKind::Command);
+        }
+
+        length += ch.len_utf16();
+        if length > MAX_CHAT_LENGTH && length - ch.len_utf16() <= MAX_CHAT_LENGTH {
+            report(ViolationKind::TooLong);
+        }
+
+        if is_forbidden(ch) {
+            report(ViolationKind::Forbidden);
+        } else if is_whitespace(ch) {
+            // A single space between two other characters is kept.
+            let kept = ch == ' '
+                && position != 0
+                && position != chars.len() - 1
+                && !is_whitespace(chars[position - 1])
+                && !is_whitespace(chars[position + 1]);
+
+            if !kept {
+                report(ViolationKind::Whitespace);
+            }
+        } else if is_invisible(ch) {
+            report(ViolationKind::Invisible);
+        } else if version >= ChatVersion::V1_19_3 && ch == '¸' {
+            report(ViolationKind::Replaced);
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// Check that a character is never rejected or altered by chat, wherever it is in a message.
+///
+/// This is false for [forbidden](ViolationKind::Forbidden) characters and [whitespace](ViolationKind::Whitespace),
+/// the same rules [validate_chat] applies.
+/// It's used by [validate_alphabet](crate::encoding::validate_alphabet), and can be used in const contexts.
+///
+/// # Examples
+///
+/// ```
+/// use ncr::validation::is_chat_safe;
+///
+/// assert!(is_chat_safe('a'));
+/// assert!(!is_chat_safe('§'));
+/// assert!(!is_chat_safe('\u{85}'));
+/// assert!(!is_chat_safe('\u{3000}'));
+/// ```
+pub const fn is_chat_safe(ch: char) -> bool {
+    !is_forbidden(ch) && !is_whitespace(ch)
+}
+
+const fn is_forbidden(ch: char) -> bool {
+    matches!(ch, '§' | '\0'..='\u{1F}' | '\u{7F}'..='\u{9F}')
+}
+
+// Const version of char::is_whitespace.
+const fn is_whitespace(ch: char) -> bool {
+    matches!(
+        ch,
+        '\t'..='\r'
+            | ' '
+            | '\u{85}'
+            | '\u{A0}'
+            | '\u{1680}'
+            | '\u{2000}'..='\u{200A}'
+            | '\u{2028}'
+            | '\u{2029}'
+            | '\u{202F}'
+            | '\u{205F}'
+            | '\u{3000}'
+    )
+}
+
+#[inline]
+fn is_invisible(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{AD}' | '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}'
+    )
+}